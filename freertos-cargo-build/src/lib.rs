@@ -21,6 +21,12 @@ const ENV_KEY_FREERTOS_CONFIG: &str = "DEP_FREERTOS_CONFIG";
 /// This variable is set by freertos-rust build.rs
 const ENV_KEY_FREERTOS_SHIM: &str = "DEP_FREERTOS_SHIM";
 
+/// Comma separated features of the freertos-rust crate that need a FreeRTOS setting.
+/// The shim is compiled with `FREERTOS_RS_FEATURE_<NAME>` defined for each of them, so
+/// it can reject a FreeRTOSConfig.h that lacks the setting.
+/// This variable is set by freertos-rust build.rs
+const ENV_KEY_FREERTOS_FEATURES: &str = "DEP_FREERTOS_FEATURES";
+
 #[derive(Clone, Debug)]
 pub struct Builder {
     freertos_dir: PathBuf,
@@ -309,9 +315,16 @@ impl Builder {
         add_build_files_with_rerun(&mut b, self.freertos_shim_files()); // Shim C file
         add_build_file_with_rerun(&mut b, self.heap_c_file()); // Heap C file
 
+        for feature in env::var(ENV_KEY_FREERTOS_FEATURES).unwrap_or_default().split(',') {
+            if !feature.is_empty() {
+                b.define(&format!("FREERTOS_RS_FEATURE_{}", feature), None);
+            }
+        }
+
         println!("cargo:rerun-if-env-changed={ENV_KEY_FREERTOS_SRC}");
         println!("cargo:rerun-if-env-changed={ENV_KEY_FREERTOS_CONFIG}");
         println!("cargo:rerun-if-env-changed={ENV_KEY_FREERTOS_SHIM}");
+        println!("cargo:rerun-if-env-changed={ENV_KEY_FREERTOS_FEATURES}");
 
         b.try_compile("freertos")
             .map_err(|e| Error::new(&format!("{}", e)))?;
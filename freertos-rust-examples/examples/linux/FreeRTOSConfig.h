@@ -91,6 +91,9 @@ extern "C" {
 #define configUSE_COUNTING_SEMAPHORES			1
 #define configUSE_QUEUE_SETS					1
 #define configUSE_TASK_NOTIFICATIONS			1
+#define configTASK_NOTIFICATION_ARRAY_ENTRIES	3
+#define configNUM_THREAD_LOCAL_STORAGE_POINTERS	1
+#define configSUPPORT_STATIC_ALLOCATION			1
 
 /* Software timer related configuration options. */
 #define configUSE_TIMERS						1
@@ -137,6 +140,7 @@ functions anyway. */
 #define INCLUDE_eTaskGetState					1
 #define INCLUDE_xSemaphoreGetMutexHolder		1
 #define INCLUDE_xTimerPendFunctionCall			1
+#define INCLUDE_xTaskAbortDelay					1
 
 /* It is a good idea to define configASSERT() while developing.  configASSERT()
 uses the same semantics as the standard C assert() macro. */
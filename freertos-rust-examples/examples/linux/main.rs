@@ -23,6 +23,17 @@ fn main() {
         }
     }).unwrap();
     println!("Task registered");
+
+    let answer = executor::spawn(Task::new().name("async").stack_size(128), async {
+        executor::sleep(Duration::ms(500)).await.unwrap();
+        let half = async { 21 }.await;
+        half * 2
+    }).unwrap();
+    Task::new().name("join").stack_size(128).start(move |_this_task| {
+        let answer = answer.join(Duration::infinite()).unwrap();
+        println!("Async task returned {}", answer);
+    }).unwrap();
+    println!("Async task registered");
     //let free = freertos_rs_xPortGetFreeHeapSize();
     // println!("Free Memory: {}!", free);
     println!("Starting scheduler");
@@ -35,7 +46,6 @@ fn main() {
 
 #[test]
 fn many_boxes() {
-    init_allocator();
     println!("many_boxes... ");
     for i in 0..10 { // .. HEAP_SIZE
         let x = Box::new(i);
@@ -43,3 +53,1008 @@ fn many_boxes() {
     }
     println!("[ok]");
 }
+
+/// Behaviour checks of the wrappers against the Linux port.
+///
+/// The scheduler can only be started once and never returns, so the checks run one
+/// after another on a single task, which exits the process when all of them passed.
+/// A failing check panics in the task, which aborts the process and fails the test.
+#[cfg(test)]
+mod checks {
+    use freertos_rust::*;
+    use std::future::{poll_fn, Future};
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::Poll;
+
+    const CHECKS: &[(&str, fn())] = &[
+        ("wakers_after_finished_task", wakers_after_finished_task),
+        ("wakers_after_dropped_future", wakers_after_dropped_future),
+        ("concurrent_async_waiters", concurrent_async_waiters),
+        ("stream_buffer", stream_buffer),
+        ("message_buffer", message_buffer),
+        ("queue_reset", queue_reset),
+        ("queue_static", queue_static),
+        ("static_objects", static_objects),
+        ("queue_set", queue_set),
+        ("mailbox", mailbox),
+        ("notifications", notifications),
+        ("indexed_notifications", indexed_notifications),
+        ("task_local", task_local),
+        ("condvar", condvar),
+        ("rw_lock", rw_lock),
+        ("scoped_tasks", scoped_tasks),
+        ("join_handle", join_handle),
+        ("task_priority_and_abort_delay", task_priority_and_abort_delay),
+        ("task_tags", task_tags),
+        ("system_state", system_state),
+        ("mutex_introspection", mutex_introspection),
+        ("critical_section", critical_section),
+        ("exclusive_data", exclusive_data),
+        ("timer_control", timer_control),
+        ("timer_delete", timer_delete),
+        ("timer_from_raw_handle", timer_from_raw_handle),
+        ("timer_daemon_pend", timer_daemon_pend),
+        ("timer_daemon_pend_from_isr", timer_daemon_pend_from_isr),
+        ("timer_fn_mut_callback", timer_fn_mut_callback),
+        ("stack_overflow_hook", stack_overflow_hook),
+    ];
+
+    #[test]
+    fn freertos() {
+        Task::new().name("checks").stack_size(128).priority(TaskPriority(2)).start(|_this_task| {
+            for (name, check) in CHECKS {
+                println!("{}... ", name);
+                check();
+                println!("[ok]");
+            }
+            std::process::exit(0);
+        }).unwrap();
+        FreeRtosUtils::start_scheduler();
+    }
+
+    /// Let the idle task delete the tasks that finished.
+    fn reap_tasks() {
+        CurrentTask::delay(Duration::ms(50));
+    }
+
+    /// Poll `future` once and drop it, whether or not it completed.
+    async fn poll_once<F: Future>(future: F) {
+        let mut future = Box::pin(future);
+        poll_fn(|cx| {
+            let _ = Pin::as_mut(&mut future).poll(cx);
+            Poll::Ready(())
+        }).await
+    }
+
+    fn wakers_after_finished_task() {
+        let queue = Arc::new(Queue::new(1).unwrap());
+        let semaphore = Arc::new(Semaphore::new_binary().unwrap());
+        let events = Arc::new(EventGroup::new().unwrap());
+        let mutex = Arc::new(Mutex::new(0u32).unwrap());
+
+        let waiter = {
+            let (queue, semaphore, events, mutex) = (queue.clone(), semaphore.clone(), events.clone(), mutex.clone());
+            executor::spawn(Task::new().name("waiter").stack_size(128), async move {
+                let item = queue.recv_async().await;
+                semaphore.acquire_async().await;
+                let bits = events.wait_bits_async(0b1, true, true).await;
+                *mutex.lock_async().await += 1;
+                (item, bits)
+            }).unwrap()
+        };
+
+        // The waiter has a lower priority, so it runs and waits between these steps.
+        CurrentTask::delay(Duration::ms(10));
+        queue.send(7u32, Duration::zero()).unwrap();
+        CurrentTask::delay(Duration::ms(10));
+        semaphore.give();
+        CurrentTask::delay(Duration::ms(10));
+        events.set_bits(0b1);
+        assert_eq!(waiter.join(Duration::ms(1000)).unwrap(), (7, 0b1));
+        reap_tasks();
+
+        // The waiter task is gone, signalling the objects must not wake it.
+        queue.send(8, Duration::zero()).unwrap();
+        assert!(semaphore.give());
+        events.set_bits(0b1);
+        drop(mutex.lock(Duration::zero()).unwrap());
+        assert_eq!(queue.receive(Duration::zero()).unwrap(), 8);
+        assert_eq!(*mutex.lock(Duration::zero()).unwrap(), 1);
+    }
+
+    fn wakers_after_dropped_future() {
+        let queue = Arc::new(Queue::<u32>::new(1).unwrap());
+        let semaphore = Arc::new(Semaphore::new_binary().unwrap());
+        let events = Arc::new(EventGroup::new().unwrap());
+
+        let waiter = {
+            let (queue, semaphore, events) = (queue.clone(), semaphore.clone(), events.clone());
+            executor::spawn(Task::new().name("waiter").stack_size(128), async move {
+                poll_once(queue.recv_async()).await;
+                poll_once(semaphore.acquire_async()).await;
+                poll_once(events.wait_bits_async(0b1, true, true)).await;
+            }).unwrap()
+        };
+        waiter.join(Duration::ms(1000)).unwrap();
+        reap_tasks();
+
+        queue.send(1, Duration::zero()).unwrap();
+        assert!(semaphore.give());
+        events.set_bits(0b1);
+        assert_eq!(queue.receive(Duration::zero()).unwrap(), 1);
+    }
+
+    fn concurrent_async_waiters() {
+        let queue = Arc::new(Queue::new(2).unwrap());
+        let mutex = Arc::new(Mutex::new(0u32).unwrap());
+        let guard = mutex.lock(Duration::zero()).unwrap();
+
+        let waiters: Vec<_> = (0..2).map(|_| {
+            let (queue, mutex) = (queue.clone(), mutex.clone());
+            executor::spawn(Task::new().name("waiter").stack_size(128), async move {
+                let item = queue.recv_async().await;
+                *mutex.lock_async().await += item;
+            }).unwrap()
+        }).collect();
+
+        // A future that stops waiting doesn't take the other waiters with it.
+        let dropped = {
+            let queue = queue.clone();
+            executor::spawn(Task::new().name("dropped").stack_size(128), async move {
+                poll_once(queue.recv_async()).await;
+            }).unwrap()
+        };
+        dropped.join(Duration::ms(1000)).unwrap();
+
+        // Both waiters get an item, then wait for the mutex.
+        CurrentTask::delay(Duration::ms(10));
+        queue.send(1u32, Duration::zero()).unwrap();
+        queue.send(2, Duration::zero()).unwrap();
+        CurrentTask::delay(Duration::ms(10));
+        assert!(queue.is_empty());
+        drop(guard);
+
+        for waiter in waiters {
+            waiter.join(Duration::ms(1000)).unwrap();
+        }
+        assert_eq!(*mutex.lock(Duration::zero()).unwrap(), 3);
+    }
+
+    fn stream_buffer() {
+        let buffer = Arc::new(StreamBuffer::new(8, 4).unwrap());
+        let mut bytes = [0; 8];
+        assert_eq!(buffer.receive(&mut bytes, Duration::ms(10)), 0);
+
+        // The receiver only wakes up once the trigger level is reached.
+        let receiver = {
+            let buffer = buffer.clone();
+            Task::new().name("receiver").stack_size(128).spawn(move |_this_task| {
+                let mut bytes = [0; 8];
+                let received = buffer.receive(&mut bytes, Duration::ms(1000));
+                bytes[..received].to_vec()
+            }).unwrap()
+        };
+        CurrentTask::delay(Duration::ms(10));
+        assert_eq!(buffer.send(&[1, 2], Duration::zero()), 2);
+        CurrentTask::delay(Duration::ms(10));
+        assert!(!receiver.is_finished());
+        assert_eq!(buffer.send(&[3, 4], Duration::zero()), 2);
+        assert_eq!(receiver.join(Duration::ms(1000)).unwrap(), [1, 2, 3, 4]);
+
+        // Sends that time out write what fits.
+        assert_eq!(buffer.send(&[0; 6], Duration::zero()), 6);
+        assert_eq!(buffer.send(&[0; 6], Duration::ms(10)), 2);
+        assert!(buffer.is_full());
+        assert!(buffer.reset());
+        assert!(buffer.is_empty());
+    }
+
+    fn message_buffer() {
+        let buffer = MessageBuffer::new(32).unwrap();
+        let mut bytes = [0; 8];
+        assert_eq!(buffer.receive(&mut bytes, Duration::ms(10)), 0);
+        assert!(buffer.receive_vec(Duration::ms(10)).is_err());
+
+        // Messages keep their boundaries.
+        buffer.send(&[1, 2, 3], Duration::zero()).unwrap();
+        buffer.send(&[4], Duration::zero()).unwrap();
+        assert_eq!(buffer.receive(&mut bytes, Duration::zero()), 3);
+        assert_eq!(bytes[..3], [1, 2, 3]);
+
+        // A message that doesn't fit stays in the buffer.
+        assert_eq!(buffer.receive(&mut [], Duration::zero()), 0);
+        assert_eq!(buffer.next_length_bytes(), 1);
+        assert_eq!(buffer.receive_vec(Duration::zero()).unwrap(), [4]);
+        assert!(buffer.is_empty());
+
+        // Empty messages are rejected instead of being lost.
+        assert_eq!(buffer.send(&[], Duration::zero()), Err(FreeRtosError::EmptyMessage));
+        assert!(buffer.is_empty());
+
+        // A receiver on another task waits for the whole message.
+        let buffer = Arc::new(buffer);
+        let receiver = {
+            let buffer = buffer.clone();
+            Task::new().name("receiver").stack_size(128).spawn(move |_this_task| {
+                buffer.receive_vec(Duration::ms(1000)).unwrap()
+            }).unwrap()
+        };
+        CurrentTask::delay(Duration::ms(10));
+        assert!(!receiver.is_finished());
+        buffer.send(&[5, 6], Duration::zero()).unwrap();
+        assert_eq!(receiver.join(Duration::ms(1000)).unwrap(), [5, 6]);
+
+        // Messages larger than the free space time out as a whole.
+        buffer.send(&[7; 16], Duration::zero()).unwrap();
+        assert!(buffer.send(&[8; 16], Duration::ms(10)).is_err());
+        assert_eq!(buffer.receive_vec(Duration::zero()).unwrap(), [7; 16]);
+        assert!(buffer.is_empty());
+    }
+
+    fn mailbox() {
+        let mailbox = Arc::new(Mailbox::new().unwrap());
+        assert!(mailbox.receive(Duration::ms(10)).is_err());
+
+        // Only the latest value is kept.
+        mailbox.overwrite(1u32);
+        mailbox.overwrite(2);
+        assert_eq!(mailbox.len(), 1);
+        assert!(mailbox.is_full());
+        assert_eq!(mailbox.peek(Duration::zero()).unwrap(), 2);
+        assert_eq!(mailbox.receive(Duration::zero()).unwrap(), 2);
+        assert!(mailbox.is_empty());
+
+        // Overwriting wakes a future waiting on the mailbox.
+        let waiter = {
+            let mailbox = mailbox.clone();
+            executor::spawn(Task::new().name("waiter").stack_size(128), async move {
+                mailbox.recv_async().await
+            }).unwrap()
+        };
+        CurrentTask::delay(Duration::ms(10));
+        mailbox.overwrite(3);
+        assert_eq!(waiter.join(Duration::ms(1000)).unwrap(), 3);
+    }
+
+    fn notifications() {
+        let this_task = Task::current().unwrap();
+        let signal = NotifySignal::new(this_task.clone(), 0);
+        let counter = NotifyCounter::new(this_task.clone(), 0);
+        let bits = EventBits::new(this_task, 0);
+        assert!(!signal.wait(Duration::ms(10)));
+
+        // Signals don't count.
+        signal.signal();
+        signal.signal();
+        assert!(signal.wait(Duration::zero()));
+        assert!(!signal.wait(Duration::zero()));
+
+        counter.give();
+        counter.give();
+        counter.give();
+        assert!(counter.take(Duration::zero()));
+        assert_eq!(counter.take_all(Duration::zero()), 2);
+        assert_eq!(counter.take_all(Duration::ms(10)), 0);
+
+        bits.set(0b01);
+        bits.set(0b10);
+        assert_eq!(bits.wait(Duration::zero()).unwrap(), 0b11);
+        assert!(bits.wait(Duration::ms(10)).is_err());
+
+        // Another task unblocks the waiting receiver.
+        let sender = {
+            let signal = signal.clone();
+            Task::new().name("sender").stack_size(128).spawn(move |_this_task| {
+                CurrentTask::delay(Duration::ms(10));
+                signal.signal();
+            }).unwrap()
+        };
+        assert!(signal.wait(Duration::ms(1000)));
+        sender.join(Duration::ms(1000)).unwrap();
+    }
+
+    fn condvar() {
+        let pair = Arc::new((Mutex::new(0u32).unwrap(), Condvar::new().unwrap()));
+        let (mutex, cond) = &*pair;
+
+        // Notifications without waiters and after a timeout are not kept.
+        cond.notify_one();
+        let guard = mutex.lock(Duration::zero()).unwrap();
+        let (guard, result) = cond.wait(guard, Duration::ms(10));
+        assert!(result.timed_out());
+        drop(guard);
+        cond.notify_all();
+        let guard = mutex.lock(Duration::zero()).unwrap();
+        let (guard, result) = cond.wait_while(guard, Duration::ms(10), |value| *value == 0);
+        assert!(result.timed_out());
+        drop(guard);
+
+        // notify_one wakes a single waiter, notify_all the rest.
+        let waiters: Vec<_> = (0..2).map(|_| {
+            let pair = pair.clone();
+            Task::new().name("waiter").stack_size(128).spawn(move |_this_task| {
+                let (mutex, cond) = &*pair;
+                let guard = mutex.lock(Duration::infinite()).unwrap();
+                let (mut guard, result) = cond.wait(guard, Duration::ms(1000));
+                *guard += 1;
+                result.timed_out()
+            }).unwrap()
+        }).collect();
+        CurrentTask::delay(Duration::ms(10));
+        cond.notify_one();
+        CurrentTask::delay(Duration::ms(10));
+        assert_eq!(*mutex.lock(Duration::zero()).unwrap(), 1);
+        cond.notify_all();
+        for waiter in waiters {
+            assert!(!waiter.join(Duration::ms(1000)).unwrap());
+        }
+        assert_eq!(*mutex.lock(Duration::zero()).unwrap(), 2);
+
+        // wait_while returns once the condition is met.
+        let notifier = {
+            let pair = pair.clone();
+            Task::new().name("notifier").stack_size(128).spawn(move |_this_task| {
+                let (mutex, cond) = &*pair;
+                *mutex.lock(Duration::infinite()).unwrap() = 0;
+                cond.notify_one();
+            }).unwrap()
+        };
+        let guard = mutex.lock(Duration::zero()).unwrap();
+        let (guard, result) = cond.wait_while(guard, Duration::ms(1000), |value| *value != 0);
+        assert!(!result.timed_out());
+        drop(guard);
+        notifier.join(Duration::ms(1000)).unwrap();
+    }
+
+    fn rw_lock() {
+        let lock = Arc::new(RwLock::new(0u32).unwrap());
+
+        // Readers share the lock, a writer times out while they hold it.
+        let first = lock.read(Duration::zero()).unwrap();
+        let second = lock.read(Duration::zero()).unwrap();
+        assert!(lock.write(Duration::ms(10)).is_err());
+        // The writer that gave up doesn't hold back new readers.
+        drop(lock.read(Duration::zero()).unwrap());
+        drop(second);
+
+        // A waiting writer blocks new readers and gets the lock once the last reader is gone.
+        let writer = {
+            let lock = lock.clone();
+            Task::new().name("writer").stack_size(128).spawn(move |_this_task| {
+                *lock.write(Duration::ms(1000)).unwrap() = 1;
+            }).unwrap()
+        };
+        CurrentTask::delay(Duration::ms(10));
+        assert!(lock.read(Duration::ms(10)).is_err());
+        assert!(!writer.is_finished());
+        drop(first);
+        writer.join(Duration::ms(1000)).unwrap();
+        assert_eq!(*lock.read(Duration::zero()).unwrap(), 1);
+
+        // Readers wait for the writer holding the lock.
+        let guard = lock.write(Duration::zero()).unwrap();
+        assert!(lock.read(Duration::ms(10)).is_err());
+        drop(guard);
+        *lock.write(Duration::zero()).unwrap() = 2;
+        assert_eq!(*lock.read(Duration::zero()).unwrap(), 2);
+    }
+
+    fn timer_control() {
+        let fired = Arc::new(Queue::new(4).unwrap());
+        let timer = {
+            let fired = fired.clone();
+            Timer::new(Duration::ms(50)).set_name("ticker").set_auto_reload(false).create(move |timer: &TimerRef| {
+                fired.send(timer.get_name().unwrap() == "ticker", Duration::zero()).unwrap();
+            }).unwrap()
+        };
+        assert_eq!(timer.get_name().unwrap(), "ticker");
+        assert_eq!(timer.get_period().to_ticks(), Duration::ms(50).to_ticks());
+        assert!(!timer.is_active());
+
+        // A reset pushes the expiry back by the time that passed.
+        timer.start(Duration::zero()).unwrap();
+        CurrentTask::delay(Duration::ms(10));
+        assert!(timer.is_active());
+        let expiry = timer.get_expiry_time();
+        timer.reset(Duration::zero()).unwrap();
+        CurrentTask::delay(Duration::ms(10));
+        assert!(timer.get_expiry_time().wrapping_sub(expiry) >= Duration::ms(10).to_ticks());
+
+        // A one-shot timer fires once and stops.
+        assert!(fired.receive(Duration::ms(1000)).unwrap());
+        assert!(!timer.is_active());
+        assert!(fired.receive(Duration::ms(100)).is_err());
+
+        // Changing the period starts the timer.
+        timer.change_period(Duration::zero(), Duration::ms(20)).unwrap();
+        assert_eq!(timer.get_period().to_ticks(), Duration::ms(20).to_ticks());
+        assert!(fired.receive(Duration::ms(1000)).unwrap());
+
+        // An auto-reload timer keeps firing until it is stopped.
+        timer.set_reload_mode(true);
+        timer.start(Duration::zero()).unwrap();
+        assert!(fired.receive(Duration::ms(1000)).unwrap());
+        assert!(fired.receive(Duration::ms(1000)).unwrap());
+        timer.stop(Duration::zero()).unwrap();
+        CurrentTask::delay(Duration::ms(10));
+        assert!(!timer.is_active());
+        fired.reset();
+        assert!(fired.receive(Duration::ms(100)).is_err());
+    }
+
+    fn timer_delete() {
+        let captured = Arc::new(());
+
+        // Deleting frees the callback once the daemon task deleted the timer.
+        let timer = {
+            let captured = captured.clone();
+            Timer::new(Duration::ms(10)).create(move |_timer: &TimerRef| {
+                assert!(Arc::strong_count(&captured) > 1);
+            }).unwrap()
+        };
+        timer.start(Duration::zero()).unwrap();
+        CurrentTask::delay(Duration::ms(50));
+        assert_eq!(Arc::strong_count(&captured), 2);
+        timer.delete(Duration::ms(100)).map_err(|(_, e)| e).unwrap();
+        CurrentTask::delay(Duration::ms(10));
+        assert_eq!(Arc::strong_count(&captured), 1);
+
+        // So does dropping the timer.
+        let timer = {
+            let captured = captured.clone();
+            Timer::new(Duration::ms(10)).create(move |_timer: &TimerRef| {
+                assert!(Arc::strong_count(&captured) > 1);
+            }).unwrap()
+        };
+        timer.start(Duration::zero()).unwrap();
+        CurrentTask::delay(Duration::ms(50));
+        drop(timer);
+        CurrentTask::delay(Duration::ms(10));
+        assert_eq!(Arc::strong_count(&captured), 1);
+
+        // Timers with a plain function callback only own their name.
+        fn tick(_timer: &TimerRef) {}
+        let timer = Timer::new(Duration::ms(10)).set_name("plain").create_fn(tick).unwrap();
+        timer.start(Duration::zero()).unwrap();
+        CurrentTask::delay(Duration::ms(50));
+        assert_eq!(timer.get_name().unwrap(), "plain");
+        timer.delete(Duration::ms(100)).map_err(|(_, e)| e).unwrap();
+        CurrentTask::delay(Duration::ms(10));
+    }
+
+    fn timer_from_raw_handle() {
+        let fired = Arc::new(Queue::new(4).unwrap());
+        let timer = {
+            let fired = fired.clone();
+            Timer::new(Duration::ms(10)).set_auto_reload(false).create(move |_timer: &TimerRef| {
+                fired.send((), Duration::zero()).unwrap();
+            }).unwrap()
+        };
+
+        // Dropping a wrapped handle neither deletes the timer nor frees its callback.
+        drop(unsafe { Timer::from_raw_handle(timer.raw_handle()) });
+        CurrentTask::delay(Duration::ms(10));
+        assert_eq!(Arc::strong_count(&fired), 2);
+        timer.start(Duration::zero()).unwrap();
+        fired.receive(Duration::ms(1000)).unwrap();
+
+        // The wrapper can be used like the timer.
+        let wrapped = unsafe { Timer::from_raw_handle(timer.raw_handle()) };
+        wrapped.start(Duration::zero()).unwrap();
+        fired.receive(Duration::ms(1000)).unwrap();
+        drop(wrapped);
+
+        drop(timer);
+        CurrentTask::delay(Duration::ms(10));
+        assert_eq!(Arc::strong_count(&fired), 1);
+    }
+
+    fn timer_daemon_pend() {
+        let done = Arc::new(Queue::new(2).unwrap());
+
+        // Pended calls run on the daemon task, in the order they were queued.
+        for call in 0..2u32 {
+            let done = done.clone();
+            TimerDaemon::pend(move || {
+                let on_daemon = Task::current().unwrap().get_name().unwrap() == "Tmr Svc";
+                done.send((call, on_daemon), Duration::zero()).unwrap();
+            }, Duration::ms(100)).unwrap();
+        }
+        assert_eq!(done.receive(Duration::ms(1000)).unwrap(), (0, true));
+        assert_eq!(done.receive(Duration::ms(1000)).unwrap(), (1, true));
+
+        // The closure is freed after it ran.
+        CurrentTask::delay(Duration::ms(10));
+        assert_eq!(Arc::strong_count(&done), 1);
+    }
+
+    fn timer_fn_mut_callback() {
+        let calls = Arc::new(Queue::new(4).unwrap());
+
+        // The callback keeps its own count and stops the timer on the third call.
+        let timer = {
+            let calls = calls.clone();
+            let mut count = 0u32;
+            Timer::new(Duration::ms(10)).create(move |timer: &TimerRef| {
+                count += 1;
+                if count == 3 {
+                    timer.stop().unwrap();
+                }
+                calls.send(count, Duration::zero()).unwrap();
+            }).unwrap()
+        };
+        timer.start(Duration::zero()).unwrap();
+        for count in 1..=3 {
+            assert_eq!(calls.receive(Duration::ms(1000)).unwrap(), count);
+        }
+        assert!(calls.receive(Duration::ms(50)).is_err());
+        assert!(!timer.is_active());
+
+        // Restarting continues the count.
+        timer.start(Duration::zero()).unwrap();
+        assert_eq!(calls.receive(Duration::ms(1000)).unwrap(), 4);
+        timer.stop(Duration::zero()).unwrap();
+    }
+
+    fn task_local() {
+        use std::cell::Cell;
+        use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+        static FAIL_INIT: AtomicBool = AtomicBool::new(true);
+        static DROPPED: AtomicU32 = AtomicU32::new(0);
+
+        struct Counter(Cell<u32>);
+
+        impl Drop for Counter {
+            fn drop(&mut self) {
+                DROPPED.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+
+        task_local! {
+            static COUNTER: Counter = {
+                if FAIL_INIT.swap(false, Ordering::SeqCst) {
+                    panic!("first initialisation fails");
+                }
+                Counter(Cell::new(0))
+            };
+        }
+
+        // A panicking initialiser runs again on the next use.
+        assert!(std::panic::catch_unwind(|| COUNTER.with(|_| ())).is_err());
+        COUNTER.with(|c| c.0.set(c.0.get() + 1));
+        assert_eq!(COUNTER.with(|c| c.0.get()), 1);
+
+        // Every task has its own value, dropped when the task ends.
+        let other = Task::new().name("local").stack_size(128).spawn(|_this_task| {
+            COUNTER.with(|c| c.0.set(c.0.get() + 5));
+            COUNTER.with(|c| c.0.get())
+        }).unwrap();
+        assert_eq!(other.join(Duration::ms(1000)).unwrap(), 5);
+        reap_tasks();
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+        assert_eq!(COUNTER.with(|c| c.0.get()), 1);
+    }
+
+    fn queue_set() {
+        #[derive(Debug, Copy, Clone, PartialEq)]
+        enum Source {
+            Commands,
+            Tick,
+            Lock,
+        }
+
+        let commands = Queue::new(2).unwrap();
+        let tick = Semaphore::new_binary().unwrap();
+        let lock = MutexNormal::create().unwrap();
+        lock.take(Duration::zero()).unwrap();
+
+        let mut set = QueueSet::<Source, 3>::new(2 + 1 + 1).unwrap();
+        set.add(&commands, Source::Commands).unwrap();
+        set.add(&tick, Source::Tick).unwrap();
+        set.add(&lock, Source::Lock).unwrap();
+        // The set is full.
+        assert!(set.add(&commands, Source::Commands).is_err());
+        assert!(set.select(Duration::ms(10)).is_err());
+
+        // Members are reported by their key, in the order they became ready.
+        commands.send(7u32, Duration::zero()).unwrap();
+        tick.give();
+        lock.give();
+        assert_eq!(set.select(Duration::zero()).unwrap(), Source::Commands);
+        assert_eq!(commands.receive(Duration::zero()).unwrap(), 7);
+        assert_eq!(set.select(Duration::zero()).unwrap(), Source::Tick);
+        tick.take(Duration::zero()).unwrap();
+        assert_eq!(set.select(Duration::zero()).unwrap(), Source::Lock);
+        lock.take(Duration::zero()).unwrap();
+        assert!(set.select(Duration::zero()).is_err());
+
+        // A removed member isn't reported anymore and can join another set.
+        assert_eq!(set.remove(&tick).unwrap(), Source::Tick);
+        assert!(set.remove(&tick).is_err());
+        tick.give();
+        assert!(set.select(Duration::zero()).is_err());
+        tick.take(Duration::zero()).unwrap();
+
+        // Dropping the set removes its members.
+        drop(set);
+        let mut other = QueueSet::<Source, 1>::new(2).unwrap();
+        other.add(&commands, Source::Commands).unwrap();
+        commands.send(8, Duration::zero()).unwrap();
+        assert_eq!(other.select(Duration::ms(1000)).unwrap(), Source::Commands);
+        assert_eq!(commands.receive(Duration::zero()).unwrap(), 8);
+    }
+
+    fn mutex_introspection() {
+        let mutex = Arc::new(Mutex::new(0u32).unwrap());
+        assert!(!mutex.is_locked());
+        assert!(mutex.holder().is_none());
+        assert!(mutex.lock_site().is_none());
+
+        // The lock site is recorded by lock and try_lock, and reset on unlock.
+        let (guard, line) = (mutex.lock(Duration::zero()).unwrap(), line!());
+        assert_eq!(mutex.lock_site().unwrap().line(), line);
+        assert_eq!(mutex.holder().unwrap().raw_handle(), Task::current().unwrap().raw_handle());
+        drop(guard);
+        assert!(mutex.lock_site().is_none());
+        let (guard, line) = (mutex.try_lock().unwrap(), line!());
+        assert_eq!(mutex.lock_site().unwrap().file(), file!());
+        assert_eq!(mutex.lock_site().unwrap().line(), line);
+        drop(guard);
+
+        // Another task holds the lock until it is told to release it.
+        let release = Arc::new(Semaphore::new_binary().unwrap());
+        let holder = {
+            let (mutex, release) = (mutex.clone(), release.clone());
+            Task::new().name("holder").stack_size(128).spawn(move |_this_task| {
+                let mut guard = mutex.lock(Duration::infinite()).unwrap();
+                *guard += 1;
+                release.take(Duration::infinite()).unwrap();
+            }).unwrap()
+        };
+        CurrentTask::delay(Duration::ms(10));
+        assert!(mutex.is_locked());
+        assert_eq!(mutex.holder().unwrap().raw_handle(), holder.task().raw_handle());
+        assert!(mutex.try_lock().is_none());
+        assert!(mutex.lock_site().is_some());
+
+        release.give();
+        holder.join(Duration::ms(1000)).unwrap();
+        assert!(!mutex.is_locked());
+        assert_eq!(*mutex.try_lock().unwrap(), 1);
+    }
+
+    fn stack_overflow_hook() {
+        use std::sync::Mutex as StdMutex;
+        static OVERFLOWED: StdMutex<Option<(usize, String)>> = StdMutex::new(None);
+
+        // The callback decides what happens to the task, here it is suspended.
+        FREERTOS_HOOKS.set_on_stack_overflow(|task, name| {
+            *OVERFLOWED.lock().unwrap() = Some((task as usize, name.to_string()));
+            CurrentTask::suspend();
+        }).unwrap();
+
+        let overflowing = Task::new().name("overflow").stack_size(128).start(|this_task| {
+            unsafe { vApplicationStackOverflowHook(this_task.raw_handle(), b"overflow\0".as_ptr() as _) };
+            unreachable!("the callback returned");
+        }).unwrap();
+        CurrentTask::delay(Duration::ms(10));
+
+        let overflowed = OVERFLOWED.lock().unwrap().take();
+        assert_eq!(overflowed, Some((overflowing.raw_handle() as usize, "overflow".to_string())));
+        assert!(matches!(overflowing.get_state(), FreeRtosTaskState::Suspended));
+        unsafe { overflowing.delete() };
+        reap_tasks();
+    }
+
+    fn critical_section() {
+        use core::cell::Cell;
+        static COUNTER: critical_section::Mutex<Cell<u32>> = critical_section::Mutex::new(Cell::new(0));
+
+        // Critical sections nest.
+        critical_section::with(|outer| {
+            critical_section::with(|inner| COUNTER.borrow(inner).set(1));
+            assert_eq!(COUNTER.borrow(outer).get(), 1);
+        });
+
+        let workers: Vec<_> = (0..2).map(|i| {
+            Task::new().name(&format!("cs{}", i)).stack_size(128).spawn(|_this_task| {
+                for _ in 0..100 {
+                    critical_section::with(|cs| {
+                        let counter = COUNTER.borrow(cs);
+                        counter.set(counter.get() + 1);
+                    });
+                    CurrentTask::delay(Duration::ticks(0));
+                }
+            }).unwrap()
+        }).collect();
+        for worker in workers {
+            worker.join(Duration::ms(1000)).unwrap();
+        }
+        assert_eq!(critical_section::with(|cs| COUNTER.borrow(cs).get()), 201);
+    }
+
+    fn queue_reset() {
+        // Items that need to be dropped are dropped by reset.
+        let item = Arc::new(0u32);
+        let queue = Queue::new(4).unwrap();
+        queue.send(item.clone(), Duration::zero()).unwrap();
+        queue.send(item.clone(), Duration::zero()).unwrap();
+        assert_eq!(Arc::strong_count(&item), 3);
+        queue.reset();
+        assert!(queue.is_empty());
+        assert_eq!(Arc::strong_count(&item), 1);
+
+        let queue = Queue::new(4).unwrap();
+        queue.send(1u32, Duration::zero()).unwrap();
+        queue.reset();
+        assert!(queue.is_empty());
+        queue.send(2, Duration::zero()).unwrap();
+        assert_eq!(queue.receive(Duration::zero()).unwrap(), 2);
+    }
+
+    fn queue_static() {
+        use std::mem::MaybeUninit;
+
+        let buffer = Box::leak(Box::new(StaticQueueBuffer::new()));
+        let storage = Box::leak(Box::new([MaybeUninit::<u32>::uninit(); 2]));
+        let queue = Queue::new_static(buffer, storage).unwrap();
+        queue.send(1, Duration::zero()).unwrap();
+        queue.send(2, Duration::zero()).unwrap();
+        assert!(queue.send(3, Duration::zero()).is_err());
+        assert_eq!(queue.receive(Duration::zero()).unwrap(), 1);
+        assert_eq!(queue.receive(Duration::zero()).unwrap(), 2);
+
+        // Storage for no items is rejected.
+        let buffer = Box::leak(Box::new(StaticQueueBuffer::new()));
+        let storage: &'static mut [MaybeUninit<u32>] = &mut [];
+        assert_eq!(Queue::new_static(buffer, storage).err(), Some(FreeRtosError::InvalidQueueSize));
+    }
+
+    fn scoped_tasks() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        // Scoped tasks borrow data from the stack of the spawning task.
+        let samples: Vec<u32> = (1..=10).collect();
+        let (low, high) = Task::scope(|s| {
+            let low = s.spawn(|_| samples[..5].iter().sum::<u32>()).unwrap();
+            let high = s.spawn(|_| samples[5..].iter().sum::<u32>()).unwrap();
+            (low.join(Duration::ms(1000)).unwrap(), high.join(Duration::ms(1000)).unwrap())
+        }).unwrap();
+        assert_eq!((low, high), (15, 40));
+
+        // The scope waits for tasks that were not joined, also when the handle is
+        // dropped or forgotten.
+        let done = AtomicU32::new(0);
+        Task::scope(|s| {
+            let slow = |_| {
+                CurrentTask::delay(Duration::ms(20));
+                done.fetch_add(1, Ordering::Relaxed);
+            };
+            drop(Task::new().name("dropped").stack_size(128).spawn_scoped(s, slow).unwrap());
+            std::mem::forget(Task::new().name("forgotten").stack_size(128).spawn_scoped(s, slow).unwrap());
+        }).unwrap();
+        assert_eq!(done.load(Ordering::Relaxed), 2);
+        reap_tasks();
+    }
+
+    fn task_priority_and_abort_delay() {
+        let sleeper = Task::new().name("sleeper").stack_size(128).priority(TaskPriority(3)).spawn(|_this_task| {
+            let start = FreeRtosUtils::get_tick_count();
+            CurrentTask::delay(Duration::ms(1000));
+            FreeRtosUtils::get_tick_count().wrapping_sub(start)
+        }).unwrap();
+        assert_eq!(sleeper.task().get_priority().unwrap().0, 3);
+        sleeper.task().set_priority(TaskPriority(4));
+        assert_eq!(sleeper.task().get_priority().unwrap().0, 4);
+
+        // A delayed task is woken early, a task that isn't blocked can't be.
+        CurrentTask::delay(Duration::ms(10));
+        assert!(sleeper.task().abort_delay());
+        let slept = sleeper.join(Duration::ms(100)).unwrap();
+        assert!(slept < 100, "slept for {} ticks", slept);
+        assert!(!Task::current().unwrap().abort_delay());
+    }
+
+    fn indexed_notifications() {
+        let this_task = Task::current().unwrap();
+        assert!(CurrentTask::wait_for_notification_indexed(1, 0, 0, Duration::ms(10)).is_err());
+
+        // Notifications at different indices don't affect each other.
+        this_task.notify(TaskNotification::SetBits(0b100));
+        this_task.notify_indexed(1, TaskNotification::SetBits(0b01)).unwrap();
+        assert_eq!(this_task.notify_and_query_indexed(1, TaskNotification::SetBits(0b10)).unwrap(), 0b01);
+        assert_eq!(CurrentTask::wait_for_notification_indexed(1, 0, 0b01, Duration::zero()).unwrap(), 0b11);
+        assert!(CurrentTask::wait_for_notification_indexed(1, 0, 0, Duration::zero()).is_err());
+        assert_eq!(this_task.clear_notification_bits_indexed(1, 0b10), 0b10);
+        assert_eq!(this_task.clear_notification_bits(u32::MAX), 0b100);
+        assert!(this_task.clear_notification_state());
+
+        this_task.notify_indexed(2, TaskNotification::Increment).unwrap();
+        this_task.notify_indexed(2, TaskNotification::Increment).unwrap();
+        assert!(this_task.clear_notification_state_indexed(2));
+        assert!(!this_task.clear_notification_state_indexed(2));
+        assert_eq!(CurrentTask::take_notification_indexed(2, false, Duration::zero()), 2);
+        assert_eq!(CurrentTask::take_notification_indexed(2, true, Duration::zero()), 1);
+        assert_eq!(CurrentTask::take_notification_indexed(2, true, Duration::zero()), 0);
+
+        // Another task unblocks the waiting receiver at its index only.
+        let sender = Task::new().name("sender").stack_size(128).spawn(move |_this_task| {
+            this_task.notify(TaskNotification::SetBits(1));
+            CurrentTask::delay(Duration::ms(10));
+            this_task.notify_indexed(1, TaskNotification::SetValue(42)).unwrap();
+        }).unwrap();
+        assert_eq!(CurrentTask::wait_for_notification_indexed(1, 0, u32::MAX, Duration::ms(1000)).unwrap(), 42);
+        sender.join(Duration::ms(1000)).unwrap();
+        assert_eq!(CurrentTask::take_notification(true, Duration::zero()), 1);
+    }
+
+    fn task_tags() {
+        extern "C" fn double(parameter: FreeRtosMutVoidPtr) -> FreeRtosBaseType {
+            parameter as FreeRtosBaseType * 2
+        }
+
+        let this_task = Task::current().unwrap();
+        assert_eq!(this_task.get_application_tag(), 0);
+        this_task.set_application_tag(7);
+        assert_eq!(this_task.get_application_tag(), 7);
+        this_task.set_application_tag(0);
+        assert_eq!(unsafe { this_task.call_hook(21 as FreeRtosMutVoidPtr) }, 0);
+
+        // Tags set on the builder are in place before the new task first runs.
+        let tagged = Task::new().name("tagged").stack_size(128).priority(TaskPriority(3)).application_tag(42).spawn(|this_task| {
+            this_task.get_application_tag()
+        }).unwrap();
+        assert_eq!(tagged.join(Duration::ms(1000)).unwrap(), 42);
+
+        let hooked = Task::new().name("hooked").stack_size(128).priority(TaskPriority(3)).hook(double).spawn(|this_task| {
+            unsafe { this_task.call_hook(21 as FreeRtosMutVoidPtr) }
+        }).unwrap();
+        assert_eq!(hooked.join(Duration::ms(1000)).unwrap(), 42);
+    }
+
+    fn system_state() {
+        let mut tasks = [std::mem::MaybeUninit::uninit(); 16];
+        let (tasks, _) = FreeRtosUtils::get_all_tasks_into(&mut tasks);
+        assert_eq!(tasks.len(), FreeRtosUtils::get_number_of_tasks());
+
+        // Names are copied out of the tasks.
+        let names: Vec<NameBuffer> = tasks.iter().map(|t| t.name().unwrap()).collect();
+        assert!(names.iter().any(|name| name.as_str() == "checks"));
+        assert!(names.iter().any(|name| name.as_str() == "IDLE"));
+    }
+
+    fn timer_daemon_pend_from_isr() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        static PENDED: AtomicU32 = AtomicU32::new(0);
+        static DAEMON: AtomicU32 = AtomicU32::new(0);
+
+        fn add(value: u32) {
+            PENDED.fetch_add(value, Ordering::Relaxed);
+            if Task::current().unwrap().get_name().unwrap() == "Tmr Svc" {
+                DAEMON.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        // Interrupt safe functions may also be called from a task.
+        {
+            let mut context = InterruptContext::new();
+            TimerDaemon::pend_from_isr(&mut context, add, 40).unwrap();
+            TimerDaemon::pend_from_isr(&mut context, add, 2).unwrap();
+        }
+        CurrentTask::delay(Duration::ms(10));
+        assert_eq!(PENDED.load(Ordering::Relaxed), 42);
+        assert_eq!(DAEMON.load(Ordering::Relaxed), 2);
+    }
+
+    fn static_objects() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+        static DONE: AtomicU32 = AtomicU32::new(0);
+        static TICKS: AtomicU32 = AtomicU32::new(0);
+
+        // Objects created in caller provided storage behave like heap allocated ones.
+        let mutex = Mutex::new_static(1u32, Box::leak(Box::new(StaticSemaphoreBuffer::new()))).unwrap();
+        *mutex.lock(Duration::zero()).unwrap() += 1;
+        assert_eq!(*mutex.lock(Duration::zero()).unwrap(), 2);
+
+        let semaphore = Semaphore::new_counting_static(2, 1, Box::leak(Box::new(StaticSemaphoreBuffer::new()))).unwrap();
+        semaphore.give();
+        assert!(semaphore.take(Duration::zero()).is_ok());
+        assert!(semaphore.take(Duration::zero()).is_ok());
+        assert!(semaphore.take(Duration::zero()).is_err());
+
+        let events = EventGroup::new_static(Box::leak(Box::new(StaticEventGroupBuffer::new()))).unwrap();
+        events.set_bits(0b101);
+        assert_eq!(events.get_bits(), 0b101);
+
+        let timer = Timer::new(Duration::ms(10))
+            .set_auto_reload(false)
+            .create_static(Box::leak(Box::new(StaticTimerBuffer::new())), |_timer| {
+                TICKS.fetch_add(1, Ordering::Relaxed);
+            })
+            .unwrap();
+        timer.start(Duration::zero()).unwrap();
+
+        let task = Task::new().name("static").start_static(
+            Box::leak(Box::new(StaticTaskBuffer::new())),
+            Box::leak(Box::new(StaticTaskStack::<256>::new())),
+            |_this_task| {
+                DONE.fetch_add(1, Ordering::Relaxed);
+                CurrentTask::suspend();
+            },
+        ).unwrap();
+        CurrentTask::delay(Duration::ms(50));
+        assert_eq!(DONE.load(Ordering::Relaxed), 1);
+        assert_eq!(TICKS.load(Ordering::Relaxed), 1);
+        assert_eq!(task.get_name().unwrap(), "static");
+        assert!(matches!(task.get_state(), FreeRtosTaskState::Suspended));
+        unsafe { task.delete() };
+        reap_tasks();
+    }
+
+    fn join_handle() {
+        let release = Arc::new(Semaphore::new_binary().unwrap());
+        let worker = {
+            let release = release.clone();
+            Task::new().name("worker").stack_size(128).spawn(move |_this_task| {
+                release.take(Duration::infinite()).unwrap();
+                String::from("result")
+            }).unwrap()
+        };
+
+        // A timed out join gives the handle back to wait again.
+        assert!(!worker.is_finished());
+        let worker = match worker.join(Duration::ms(10)) {
+            Err(JoinError::Timeout(worker)) => worker,
+            other => panic!("expected a timeout, got {:?}", other.map(|_| ())),
+        };
+        release.give();
+        CurrentTask::delay(Duration::ms(10));
+        assert!(worker.is_finished());
+        assert_eq!(worker.join(Duration::zero()).unwrap(), "result");
+
+        // With the std feature, a panic is reported instead of a result.
+        let panicking = Task::new().name("panicking").stack_size(128).spawn(|_this_task| -> u32 {
+            panic!("expected panic");
+        }).unwrap();
+        assert!(matches!(panicking.join(Duration::ms(1000)), Err(JoinError::Panicked)));
+        reap_tasks();
+    }
+
+    fn exclusive_data() {
+        let data = Arc::new(ExclusiveData::new(0u32));
+
+        // Regions from interrupts nest and restore the previous mask when dropped.
+        {
+            let mut context = InterruptContext::new();
+            let mut outer = data.lock_from_isr(&mut context).unwrap();
+            *outer += 1;
+            {
+                let _inner = CriticalRegionIsr::enter(&mut context);
+            }
+            *outer += 1;
+        }
+        assert_eq!(*data.lock().unwrap(), 2);
+
+        // Tasks and interrupts share the data.
+        let workers: Vec<_> = (0..2).map(|i| {
+            let data = data.clone();
+            Task::new().name(&format!("excl{}", i)).stack_size(128).spawn(move |_this_task| {
+                for _ in 0..100 {
+                    if i == 0 {
+                        *data.lock().unwrap() += 1;
+                    } else {
+                        let mut context = InterruptContext::new();
+                        *data.lock_from_isr(&mut context).unwrap() += 1;
+                    }
+                    CurrentTask::delay(Duration::ticks(0));
+                }
+            }).unwrap()
+        }).collect();
+        for worker in workers {
+            worker.join(Duration::ms(1000)).unwrap();
+        }
+        assert_eq!(*data.lock().unwrap(), 202);
+    }
+}
@@ -12,4 +12,20 @@ fn main() {
             .to_str()
             .unwrap()
     );
+
+    // Features that need a FreeRTOS setting, checked by the shim when it is compiled
+    // with freertos-cargo-build.
+    let features: Vec<&str> = [
+        "TASK_LOCAL",
+        "QUEUE_SET",
+        "MUTEX_HOLDER",
+        "ABORT_DELAY",
+        "TASK_TAG",
+        "STATIC_ALLOC",
+    ]
+    .iter()
+    .copied()
+    .filter(|f| env::var_os(format!("CARGO_FEATURE_{}", f)).is_some())
+    .collect();
+    println!("cargo:FEATURES={}", features.join(","));
 }
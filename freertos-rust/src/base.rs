@@ -13,6 +13,9 @@ pub enum FreeRtosError {
     TaskNotFound,
     InvalidQueueSize,
     ProcessorHasShutDown,
+    InvalidQueueSetMember,
+    StaticBufferTooSmall,
+    EmptyMessage,
 }
 
 pub type FreeRtosVoidPtr = *const c_void;
@@ -29,11 +32,16 @@ pub type FreeRtosBaseTypeMutPtr = *mut FreeRtosBaseType;
 pub type FreeRtosTaskHandle = *const c_void;
 pub type FreeRtosQueueHandle = *const c_void;
 pub type FreeRtosSemaphoreHandle = *const c_void;
+pub type FreeRtosQueueSetHandle = *const c_void;
+pub type FreeRtosQueueSetMemberHandle = *const c_void;
 pub type FreeRtosEventGroupHandle = *const c_void;
 pub type FreeRtosTaskFunction = *const c_void;
 pub type FreeRtosTimerHandle = *const c_void;
 pub type FreeRtosTimerCallback = *const c_void;
-#[allow(dead_code)]
+pub type TaskHookFunction = extern "C" fn(FreeRtosMutVoidPtr) -> FreeRtosBaseType;
+pub type FreeRtosPendedFunction = extern "C" fn(FreeRtosMutVoidPtr, u32);
+pub type FreeRtosStreamBufferHandle = *const c_void;
+pub type FreeRtosMessageBufferHandle = *const c_void;
 pub type FreeRtosStackType = *const c_void;
 
 pub type FreeRtosUnsignedLong = u32;
@@ -66,4 +74,6 @@ pub enum FreeRtosTaskState {
     Suspended = 3,
     /// The task being queried has been deleted, but its TCB has not yet been freed.
     Deleted = 4,
+    /// Used as an 'invalid state' value.
+    Invalid = 5,
 }
@@ -0,0 +1,148 @@
+use crate::base::*;
+use crate::mutex::*;
+use crate::prelude::v1::*;
+use crate::semaphore::*;
+use crate::task::*;
+use crate::units::*;
+
+/// Result of a wait on a [`Condvar`] that can time out.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WaitTimeoutResult(bool);
+
+impl WaitTimeoutResult {
+    /// Returns `true` if the wait ended because the timeout elapsed.
+    pub fn timed_out(&self) -> bool {
+        self.0
+    }
+}
+
+/// A condition variable, used together with a [`Mutex`] to wait for a condition on
+/// the protected value.
+///
+/// Waiting tasks block on a counting semaphore that is given once per notified waiter.
+/// Like any condition variable, a wait may return without a matching notification, so
+/// the condition must be checked again afterwards. [`Condvar::wait_while`] does that.
+///
+/// Sample usage:
+///
+/// ```rust
+/// # use freertos_rs::*;
+/// let ready = Mutex::new(false).unwrap();
+/// let cond = Condvar::new().unwrap();
+///
+/// // in the producer task
+/// *ready.lock(Duration::infinite()).unwrap() = true;
+/// cond.notify_one();
+///
+/// // in the consumer task
+/// let guard = ready.lock(Duration::infinite()).unwrap();
+/// let (guard, result) = cond.wait_while(guard, Duration::ms(100), |ready| !*ready);
+/// ```
+pub struct Condvar {
+    /// Number of tasks waiting for the signal that have not been notified yet.
+    waiters: Mutex<u32>,
+    signal: Semaphore,
+}
+
+impl Condvar {
+    pub fn new() -> Result<Condvar, FreeRtosError> {
+        Ok(Condvar {
+            waiters: Mutex::new(0)?,
+            signal: Semaphore::new_counting(u32::MAX, 0)?,
+        })
+    }
+
+    /// Unlock the mutex and wait for a notification, then lock the mutex again.
+    ///
+    /// Only the wait for the notification is limited by `max_wait`, the mutex is always
+    /// locked again before returning.
+    pub fn wait<'a, T: ?Sized, D: DurationTicks>(
+        &self,
+        guard: MutexGuard<'a, T, MutexNormal>,
+        max_wait: D,
+    ) -> (MutexGuard<'a, T, MutexNormal>, WaitTimeoutResult) {
+        *self.lock_waiters() += 1;
+
+        guard.unlocked(|| {
+            if self.signal.take(max_wait).is_ok() {
+                return WaitTimeoutResult(false);
+            }
+
+            let mut waiters = self.lock_waiters();
+            // A notification given after the timeout still belongs to a waiter. Take it
+            // so it doesn't wake a later waiter without a notification of its own.
+            if self.signal.take(Duration::zero()).is_ok() {
+                WaitTimeoutResult(false)
+            } else {
+                *waiters -= 1;
+                WaitTimeoutResult(true)
+            }
+        })
+    }
+
+    /// Wait as long as `condition` returns `true`, or until `max_wait` has passed in total.
+    ///
+    /// The condition is checked with the mutex locked, before the first wait and after
+    /// each notification.
+    pub fn wait_while<'a, T: ?Sized, D: DurationTicks, F>(
+        &self,
+        mut guard: MutexGuard<'a, T, MutexNormal>,
+        max_wait: D,
+        mut condition: F,
+    ) -> (MutexGuard<'a, T, MutexNormal>, WaitTimeoutResult)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let max_wait = max_wait.to_ticks();
+        let infinite = max_wait == Duration::infinite().to_ticks();
+        let start = FreeRtosUtils::get_tick_count();
+
+        while condition(&mut *guard) {
+            let remaining = if infinite {
+                max_wait
+            } else {
+                let elapsed = FreeRtosUtils::get_tick_count().wrapping_sub(start);
+                if elapsed >= max_wait {
+                    return (guard, WaitTimeoutResult(true));
+                }
+                max_wait - elapsed
+            };
+
+            guard = self.wait(guard, Duration::ticks(remaining)).0;
+        }
+
+        (guard, WaitTimeoutResult(false))
+    }
+
+    /// Wake up one waiting task, if any.
+    pub fn notify_one(&self) {
+        let mut waiters = self.lock_waiters();
+        if *waiters > 0 {
+            *waiters -= 1;
+            self.signal.give();
+        }
+    }
+
+    /// Wake up all waiting tasks.
+    pub fn notify_all(&self) {
+        let mut waiters = self.lock_waiters();
+        for _ in 0..*waiters {
+            self.signal.give();
+        }
+        *waiters = 0;
+    }
+
+    fn lock_waiters(&self) -> MutexGuard<'_, u32, MutexNormal> {
+        loop {
+            if let Ok(waiters) = self.waiters.lock(Duration::infinite()) {
+                return waiters;
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Condvar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Condvar")
+    }
+}
@@ -1,4 +1,5 @@
 use crate::base::*;
+use crate::isr::*;
 use crate::prelude::v1::*;
 use crate::shim::*;
 
@@ -21,6 +22,60 @@ impl Drop for CriticalRegion {
     }
 }
 
+/// A critical region entered from an interrupt. Masks interrupts up to
+/// `configMAX_SYSCALL_INTERRUPT_PRIORITY` and restores the previous mask when dropped,
+/// so it can be nested in interrupts of different priorities.
+pub struct CriticalRegionIsr {
+    saved_interrupt_status: FreeRtosUBaseType,
+}
+
+impl CriticalRegionIsr {
+    pub fn enter(_context: &mut InterruptContext) -> Self {
+        let saved_interrupt_status = unsafe { freertos_rs_enter_critical_from_isr() };
+
+        CriticalRegionIsr {
+            saved_interrupt_status,
+        }
+    }
+}
+
+impl Drop for CriticalRegionIsr {
+    fn drop(&mut self) {
+        unsafe {
+            freertos_rs_exit_critical_from_isr(self.saved_interrupt_status);
+        }
+    }
+}
+
+/// Implementation of the `critical-section` crate. Interrupts save and restore their
+/// interrupt mask, tasks use the nesting critical region of the kernel.
+#[cfg(feature = "critical-section")]
+struct FreeRtosCriticalSection;
+
+#[cfg(feature = "critical-section")]
+critical_section::set_impl!(FreeRtosCriticalSection);
+
+#[cfg(feature = "critical-section")]
+unsafe impl critical_section::Impl for FreeRtosCriticalSection {
+    unsafe fn acquire() -> critical_section::RawRestoreState {
+        if freertos_rs_is_in_isr() != 0 {
+            freertos_rs_enter_critical_from_isr()
+        } else {
+            freertos_rs_enter_critical();
+            0
+        }
+    }
+
+    unsafe fn release(restore_state: critical_section::RawRestoreState) {
+        // Released from the same context it was acquired in.
+        if freertos_rs_is_in_isr() != 0 {
+            freertos_rs_exit_critical_from_isr(restore_state);
+        } else {
+            freertos_rs_exit_critical();
+        }
+    }
+}
+
 unsafe impl<T: Send> Send for ExclusiveData<T> {}
 unsafe impl<T: Send> Sync for ExclusiveData<T> {}
 
@@ -46,9 +101,16 @@ impl<T> ExclusiveData<T> {
 
     pub fn lock_from_isr(
         &self,
-        _context: &mut crate::isr::InterruptContext,
+        context: &mut InterruptContext,
     ) -> Result<ExclusiveDataGuardIsr<'_, T>, FreeRtosError> {
-        Ok(ExclusiveDataGuardIsr { __data: &self.data })
+        Ok(ExclusiveDataGuardIsr {
+            __data: &self.data,
+            __lock: CriticalRegionIsr::enter(context),
+        })
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
     }
 }
 
@@ -72,8 +134,10 @@ impl<'mutex, T: ?Sized> DerefMut for ExclusiveDataGuard<'mutex, T> {
     }
 }
 
+/// Holds the critical region entered from an interrupt until we are dropped
 pub struct ExclusiveDataGuardIsr<'a, T: ?Sized + 'a> {
     __data: &'a UnsafeCell<T>,
+    __lock: CriticalRegionIsr,
 }
 
 impl<'mutex, T: ?Sized> Deref for ExclusiveDataGuardIsr<'mutex, T> {
@@ -1,10 +1,24 @@
 use crate::base::*;
+use crate::isr::*;
 use crate::shim::*;
+#[cfg(feature = "static_alloc")]
+use crate::static_alloc::*;
 use crate::units::*;
+#[cfg(feature = "executor")]
+use crate::waker::*;
+
+#[cfg(feature = "executor")]
+use core::future::poll_fn;
+#[cfg(feature = "executor")]
+use core::pin::pin;
+#[cfg(feature = "executor")]
+use core::task::Poll;
 
 /// An event group
 pub struct EventGroup {
     event_group: FreeRtosEventGroupHandle,
+    #[cfg(feature = "executor")]
+    waker: WakerList,
 }
 
 unsafe impl Send for EventGroup {}
@@ -18,7 +32,19 @@ impl EventGroup {
             if s == 0 as *const _ {
                 return Err(FreeRtosError::OutOfMemory);
             }
-            Ok(EventGroup { event_group: s })
+            Ok(EventGroup::from_raw_handle(s))
+        }
+    }
+
+    /// Create a new event group in caller provided storage
+    #[cfg(feature = "static_alloc")]
+    pub fn new_static(buffer: &'static mut StaticEventGroupBuffer) -> Result<EventGroup, FreeRtosError> {
+        unsafe {
+            let s = freertos_rs_event_group_create_static(buffer.as_mut_ptr()?);
+            if s == 0 as *const _ {
+                return Err(FreeRtosError::OutOfMemory);
+            }
+            Ok(EventGroup::from_raw_handle(s))
         }
     }
 
@@ -27,7 +53,11 @@ impl EventGroup {
     /// `handle` must be a valid FreeRTOS event group handle.
     #[inline]
     pub unsafe fn from_raw_handle(handle: FreeRtosEventGroupHandle) -> Self {
-        Self { event_group: handle }
+        Self {
+            event_group: handle,
+            #[cfg(feature = "executor")]
+            waker: WakerList::new(),
+        }
     }
     #[inline]
     pub fn raw_handle(&self) -> FreeRtosEventGroupHandle {
@@ -35,7 +65,30 @@ impl EventGroup {
     }
 
     pub fn set_bits(&self, bits_to_set: FreeRtosEventBitsType) -> FreeRtosEventBitsType {
-        unsafe { freertos_rs_event_group_set_bits(self.event_group, bits_to_set) }
+        let bits = unsafe { freertos_rs_event_group_set_bits(self.event_group, bits_to_set) };
+
+        #[cfg(feature = "executor")]
+        self.waker.wake();
+
+        bits
+    }
+
+    /// Set bits from an interrupt. The bits are set by the timer daemon task, so this
+    /// requires `configUSE_TIMERS` and `INCLUDE_xTimerPendFunctionCall`.
+    ///
+    /// Returns `true` on success, `false` when the timer command queue is full.
+    pub fn set_bits_from_isr(&self, context: &mut InterruptContext, bits_to_set: FreeRtosEventBitsType) -> bool {
+        let pended = unsafe {
+            freertos_rs_event_group_set_bits_isr(self.event_group, bits_to_set, context.get_task_field_mut()) == 0
+        };
+
+        // Queued behind the command above, so the bits are set when the future is woken.
+        #[cfg(feature = "executor")]
+        if pended {
+            self.waker.pend_wake_from_isr(context);
+        }
+
+        pended
     }
 
     pub fn get_bits(&self) -> FreeRtosEventBitsType {
@@ -50,6 +103,42 @@ impl EventGroup {
         unsafe { freertos_rs_event_group_wait_bits(self.event_group, bits_to_wait_for, clear_on_exit, wait_for_all_bits, duration.to_ticks()) }
     }
 
+    /// Wait for bits to be set without blocking the task. Returns the bits that were
+    /// set when the wait condition was met, before any were cleared.
+    ///
+    /// The future is woken by `set_bits` and `set_bits_from_isr` of this handle. Any
+    /// number of futures may wait on an event group. Futures that are not run by
+    /// [`crate::executor`] are woken by `set_bits_from_isr` before the bits are set, so
+    /// they may miss them.
+    #[cfg(feature = "executor")]
+    pub async fn wait_bits_async(&self, bits_to_wait_for: FreeRtosEventBitsType, clear_on_exit: bool, wait_for_all_bits: bool) -> FreeRtosEventBitsType {
+        let try_wait = || {
+            let bits = self.wait_bits(bits_to_wait_for, clear_on_exit as FreeRtosBaseType, wait_for_all_bits as FreeRtosBaseType, Duration::zero());
+            let done = if wait_for_all_bits {
+                bits & bits_to_wait_for == bits_to_wait_for
+            } else {
+                bits & bits_to_wait_for != 0
+            };
+            if done { Some(bits) } else { None }
+        };
+
+        let registration = pin!(self.waker.registration());
+        poll_fn(|cx| {
+            if let Some(bits) = try_wait() {
+                return Poll::Ready(bits);
+            }
+
+            registration.as_ref().register(cx.waker());
+
+            // Bits set before the waker was registered didn't wake anyone.
+            match try_wait() {
+                Some(bits) => Poll::Ready(bits),
+                None => Poll::Pending,
+            }
+        })
+        .await
+    }
+
     pub fn sync<D: DurationTicks>(&self, bits_to_set: FreeRtosEventBitsType, bits_to_wait_for: FreeRtosEventBitsType, duration: D) -> FreeRtosEventBitsType {
         unsafe { freertos_rs_event_group_sync(self.event_group, bits_to_set, bits_to_wait_for, duration.to_ticks()) }
     }
@@ -0,0 +1,234 @@
+//! Run `async` code on FreeRTOS tasks.
+//!
+//! Every future runs on its own task, which sleeps on a task notification while the
+//! future is pending. Waking the future notifies the task on notification index 0, or
+//! the index passed to [`block_on_indexed`] and [`spawn_indexed`]. That notification
+//! value must not be used for anything else, for example by a [`crate::NotifySignal`].
+//!
+//! Wakers may outlive the future and its task. Once [`block_on`] returned, waking them
+//! does nothing.
+//!
+//! Sample usage:
+//!
+//! ```rust
+//! # use freertos_rs::*;
+//! let handle = executor::spawn(Task::new().name("async"), async {
+//!     20 + 22
+//! }).unwrap();
+//!
+//! let answer = handle.join(Duration::infinite()).unwrap();
+//! ```
+//!
+//! Queues, semaphores, mutexes and event groups get `async` variants of their blocking
+//! methods with this feature. They are woken by the Rust API of the object, including
+//! its `_from_isr` methods, but not when the object is used directly from C.
+
+use crate::base::*;
+use crate::isr::*;
+use crate::join_handle::*;
+use crate::notify::*;
+use crate::prelude::v1::*;
+use crate::shim::*;
+use crate::task::*;
+#[cfg(feature = "time")]
+use crate::timers::*;
+use crate::units::*;
+#[cfg(feature = "time")]
+use crate::waker::*;
+
+use core::future::Future;
+#[cfg(feature = "time")]
+use core::future::poll_fn;
+use core::pin::pin;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+/// The task woken by the wakers of one [`block_on`] call, shared by all of them.
+struct WakeTarget {
+    signal: NotifySignal,
+    /// Cleared before `block_on` returns, after which the task may be deleted.
+    running: AtomicBool,
+}
+
+impl WakeTarget {
+    fn wake(&self) {
+        // The task can't return from `block_on` while the scheduler is suspended.
+        unsafe { freertos_rs_vTaskSuspendAll() };
+        if self.running.load(Ordering::Acquire) {
+            self.signal.signal();
+        }
+        unsafe { freertos_rs_xTaskResumeAll() };
+    }
+
+    fn wake_from_isr(&self, context: &mut InterruptContext) {
+        // No task runs until the interrupt returns.
+        if self.running.load(Ordering::Acquire) {
+            self.signal.signal_from_isr(context);
+        }
+    }
+}
+
+static VTABLE: RawWakerVTable = RawWakerVTable::new(clone_waker, wake_waker, wake_by_ref, drop_waker);
+
+unsafe fn clone_waker(target: *const ()) -> RawWaker {
+    Arc::increment_strong_count(target as *const WakeTarget);
+    RawWaker::new(target, &VTABLE)
+}
+
+unsafe fn wake_waker(target: *const ()) {
+    wake_by_ref(target);
+    drop_waker(target);
+}
+
+unsafe fn wake_by_ref(target: *const ()) {
+    (*(target as *const WakeTarget)).wake();
+}
+
+unsafe fn drop_waker(target: *const ()) {
+    Arc::decrement_strong_count(target as *const WakeTarget);
+}
+
+/// Wake `waker` from an interrupt. Wakers of this executor notify their task with the
+/// interrupt safe API; other wakers are called as they are and must support it.
+pub(crate) fn wake_from_isr(waker: &Waker, context: &mut InterruptContext) {
+    if waker.vtable() == &VTABLE {
+        let target = unsafe { &*(waker.data() as *const WakeTarget) };
+        target.wake_from_isr(context);
+    } else {
+        waker.wake_by_ref();
+    }
+}
+
+/// Wake `waker` from the timer daemon task, after the commands that the interrupt queued
+/// before, such as the bits set by `EventGroup::set_bits_from_isr`. Requires
+/// `INCLUDE_xTimerPendFunctionCall`. Other wakers are woken right away.
+pub(crate) fn pend_wake_from_isr(waker: &Waker, context: &mut InterruptContext) {
+    if waker.vtable() != &VTABLE {
+        waker.wake_by_ref();
+        return;
+    }
+
+    let target = waker.data();
+    unsafe {
+        // The reference is handed to `wake_pended`.
+        Arc::increment_strong_count(target as *const WakeTarget);
+        if freertos_rs_pend_function_call_isr(wake_pended, target as *mut _, 0, context.get_task_field_mut()) != 0 {
+            (*(target as *const WakeTarget)).wake_from_isr(context);
+            // Never the last reference, `waker` holds one.
+            Arc::decrement_strong_count(target as *const WakeTarget);
+        }
+    }
+}
+
+extern "C" fn wake_pended(target: FreeRtosMutVoidPtr, _: u32) {
+    unsafe { Waker::from_raw(RawWaker::new(target as *const (), &VTABLE)) }.wake();
+}
+
+/// Marks the wake target as finished when `block_on` returns or unwinds.
+struct Running<'a>(&'a WakeTarget);
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        unsafe { freertos_rs_vTaskSuspendAll() };
+        self.0.running.store(false, Ordering::Release);
+        unsafe { freertos_rs_xTaskResumeAll() };
+    }
+}
+
+/// Run a future to completion on the current task, blocking it while the future is pending.
+///
+/// Panics if not called from a task.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    block_on_indexed(0, future)
+}
+
+/// Run a future to completion on the current task, sleeping on the notification value
+/// at `index` while the future is pending.
+///
+/// Indices other than 0 require FreeRTOS 10.4 or later and
+/// `configTASK_NOTIFICATION_ARRAY_ENTRIES` to be large enough.
+///
+/// Panics if not called from a task.
+pub fn block_on_indexed<F: Future>(index: u32, future: F) -> F::Output {
+    let task = Task::current().expect("block_on must be called from a task");
+    let target = Arc::new(WakeTarget {
+        signal: NotifySignal::new(task, index),
+        running: AtomicBool::new(true),
+    });
+    let _running = Running(&target);
+
+    let waker = unsafe {
+        let raw = Arc::into_raw(target.clone()) as *const ();
+        Waker::from_raw(RawWaker::new(raw, &VTABLE))
+    };
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+            return output;
+        }
+
+        target.signal.wait(Duration::infinite());
+    }
+}
+
+/// Wait for `duration` without blocking the task, using a one-shot software timer.
+#[cfg(feature = "time")]
+pub async fn sleep<D: DurationTicks>(duration: D) -> Result<(), FreeRtosError> {
+    let ticks = duration.to_ticks();
+    if ticks == 0 {
+        return Ok(());
+    }
+
+    let state = Arc::new((AtomicBool::new(false), WakerList::new()));
+    let timer_state = state.clone();
+    let timer = Timer::new(Duration::ticks(ticks))
+        .set_auto_reload(false)
+        .create(move |_timer| {
+            timer_state.0.store(true, Ordering::Release);
+            timer_state.1.wake();
+        })?;
+    timer.start(Duration::infinite())?;
+
+    let registration = pin!(state.1.registration());
+    poll_fn(|cx| {
+        if state.0.load(Ordering::Acquire) {
+            return Poll::Ready(());
+        }
+
+        registration.as_ref().register(cx.waker());
+
+        if state.0.load(Ordering::Acquire) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+
+    Ok(())
+}
+
+/// Start a new task with the settings of `builder` that runs `future` to completion.
+pub fn spawn<F>(builder: &TaskBuilder, future: F) -> Result<JoinHandle<F::Output>, FreeRtosError>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    spawn_indexed(builder, 0, future)
+}
+
+/// Start a new task that runs `future` to completion, sleeping on the notification value
+/// at `index`. See [`block_on_indexed`].
+pub fn spawn_indexed<F>(
+    builder: &TaskBuilder,
+    index: u32,
+    future: F,
+) -> Result<JoinHandle<F::Output>, FreeRtosError>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    builder.spawn(move |_this_task| block_on_indexed(index, future))
+}
@@ -11,6 +11,8 @@ STM32 example:
 #include "queue.h"
 #include "semphr.h"
 #include "event_groups.h"
+#include "stream_buffer.h"
+#include "message_buffer.h"
 
 // Just for testing
 void freertos_rs_invoke_configASSERT() {
@@ -73,6 +75,12 @@ uint8_t freertos_rs_sizeof(uint8_t _type) {
 		case 25:
 			return sizeof(TimerCallbackFunction_t);
 			break;
+		case 26:
+			return sizeof(StreamBufferHandle_t);
+			break;
+		case 27:
+			return sizeof(MessageBufferHandle_t);
+			break;
 
 		case 30:
 			return sizeof(TaskStatus_t);
@@ -86,6 +94,9 @@ uint8_t freertos_rs_sizeof(uint8_t _type) {
 		case 33:
 			return sizeof(unsigned short);
 			break;
+		case 34:
+			return sizeof(StackType_t);
+			break;
 
 
 			break;
@@ -126,6 +137,21 @@ TickType_t freertos_rs_get_portTICK_PERIOD_MS() {
 	return portTICK_PERIOD_MS;
 }
 
+size_t freertos_rs_sizeof_static(uint8_t _type) {
+	switch (_type) {
+		case 0:
+			return sizeof(StaticTask_t);
+		case 1:
+			return sizeof(StaticQueue_t);
+		case 2:
+			return sizeof(StaticTimer_t);
+		case 3:
+			return sizeof(StaticEventGroup_t);
+		default:
+			return 0;
+	}
+}
+
 UBaseType_t freertos_rs_get_number_of_tasks() {
 	return uxTaskGetNumberOfTasks();
 }
@@ -155,6 +181,20 @@ SemaphoreHandle_t freertos_rs_create_mutex() {
 	return xSemaphoreCreateMutex();
 }
 
+#if defined(FREERTOS_RS_FEATURE_MUTEX_HOLDER) && (INCLUDE_xSemaphoreGetMutexHolder != 1)
+#error "The mutex_holder feature requires INCLUDE_xSemaphoreGetMutexHolder"
+#endif
+
+#if (INCLUDE_xSemaphoreGetMutexHolder == 1)
+TaskHandle_t freertos_rs_get_mutex_holder(SemaphoreHandle_t mutex) {
+	return xSemaphoreGetMutexHolder(mutex);
+}
+
+TaskHandle_t freertos_rs_get_mutex_holder_isr(SemaphoreHandle_t mutex) {
+	return xSemaphoreGetMutexHolderFromISR(mutex);
+}
+#endif
+
 SemaphoreHandle_t freertos_rs_create_binary_semaphore() {
 	return xSemaphoreCreateBinary();
 }
@@ -199,9 +239,8 @@ UBaseType_t freertos_rs_give_semaphore_isr(SemaphoreHandle_t semaphore, BaseType
 	return 1;
 }
 
-
-UBaseType_t freertos_rs_spawn_task(TaskFunction_t entry_point, void* pvParameters, const char * const name, uint8_t name_len, uint16_t stack_size, UBaseType_t priority, TaskHandle_t* task_handle) {
-	char c_name[configMAX_TASK_NAME_LEN] = {0};
+// Copy a name that is not nul-terminated, truncated to configMAX_TASK_NAME_LEN.
+static void freertos_rs_copy_task_name(char* c_name, const char * const name, uint8_t name_len) {
 	for (int i = 0; i < name_len; i++) {
 		c_name[i] = name[i];
 
@@ -209,6 +248,11 @@ UBaseType_t freertos_rs_spawn_task(TaskFunction_t entry_point, void* pvParameter
 			break;
 		}
 	}
+}
+
+UBaseType_t freertos_rs_spawn_task(TaskFunction_t entry_point, void* pvParameters, const char * const name, uint8_t name_len, uint16_t stack_size, UBaseType_t priority, TaskHandle_t* task_handle) {
+	char c_name[configMAX_TASK_NAME_LEN] = {0};
+	freertos_rs_copy_task_name(c_name, name, name_len);
 
 	BaseType_t ret = xTaskCreate(entry_point, c_name, stack_size, pvParameters, priority, task_handle);
 
@@ -231,6 +275,96 @@ void freertos_rs_suspend_task(TaskHandle_t task) {
 	vTaskSuspend(task);
 }
 
+#if (INCLUDE_vTaskSuspend == 1)
+void freertos_rs_resume_task(TaskHandle_t task) {
+	vTaskResume(task);
+}
+#endif
+
+#if (INCLUDE_xTaskResumeFromISR == 1)
+void freertos_rs_resume_task_isr(TaskHandle_t task, BaseType_t* xHigherPriorityTaskWoken) {
+	if (xTaskResumeFromISR(task) == pdTRUE) {
+		*xHigherPriorityTaskWoken = pdTRUE;
+	}
+}
+#endif
+
+#if (INCLUDE_uxTaskPriorityGet == 1)
+UBaseType_t freertos_rs_task_priority_get(TaskHandle_t task) {
+	return uxTaskPriorityGet(task);
+}
+#endif
+
+#if (INCLUDE_vTaskPrioritySet == 1)
+void freertos_rs_task_priority_set(TaskHandle_t task, UBaseType_t priority) {
+	vTaskPrioritySet(task, priority);
+}
+#endif
+
+#if (INCLUDE_eTaskGetState == 1)
+uint8_t freertos_rs_task_get_state(TaskHandle_t task) {
+	return (uint8_t) eTaskGetState(task);
+}
+#endif
+
+#if defined(FREERTOS_RS_FEATURE_ABORT_DELAY) && (INCLUDE_xTaskAbortDelay != 1)
+#error "The abort_delay feature requires INCLUDE_xTaskAbortDelay"
+#endif
+
+#if (INCLUDE_xTaskAbortDelay == 1)
+UBaseType_t freertos_rs_task_abort_delay(TaskHandle_t task) {
+	if (xTaskAbortDelay(task) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+#endif
+
+#if defined(FREERTOS_RS_FEATURE_TASK_TAG) && (configUSE_APPLICATION_TASK_TAG != 1)
+#error "The task_tag feature requires configUSE_APPLICATION_TASK_TAG"
+#endif
+
+#if (configUSE_APPLICATION_TASK_TAG == 1)
+void freertos_rs_task_set_application_tag(TaskHandle_t task, void* tag) {
+	vTaskSetApplicationTaskTag(task, (TaskHookFunction_t) tag);
+}
+
+void* freertos_rs_task_get_application_tag(TaskHandle_t task) {
+	return (void*) xTaskGetApplicationTaskTag(task);
+}
+
+BaseType_t freertos_rs_task_call_application_hook(TaskHandle_t task, void* parameter) {
+	return xTaskCallApplicationTaskHook(task, parameter);
+}
+#endif
+
+#if defined(FREERTOS_RS_FEATURE_TASK_LOCAL) && !(configNUM_THREAD_LOCAL_STORAGE_POINTERS > 0)
+#error "The task_local feature requires configNUM_THREAD_LOCAL_STORAGE_POINTERS > 0"
+#endif
+
+#if (configNUM_THREAD_LOCAL_STORAGE_POINTERS > 0)
+void freertos_rs_task_set_tls_pointer(TaskHandle_t task, BaseType_t index, void* value) {
+	vTaskSetThreadLocalStoragePointer(task, index, value);
+}
+
+void* freertos_rs_task_get_tls_pointer(TaskHandle_t task, BaseType_t index) {
+	return pvTaskGetThreadLocalStoragePointer(task, index);
+}
+
+// Slot used by task_local!, can be moved by defining it in FreeRTOSConfig.h.
+#ifndef FREERTOS_RS_TASK_LOCAL_INDEX
+#define FREERTOS_RS_TASK_LOCAL_INDEX 0
+#endif
+
+#if (FREERTOS_RS_TASK_LOCAL_INDEX < 0) || (FREERTOS_RS_TASK_LOCAL_INDEX >= configNUM_THREAD_LOCAL_STORAGE_POINTERS)
+#error "FREERTOS_RS_TASK_LOCAL_INDEX must be below configNUM_THREAD_LOCAL_STORAGE_POINTERS"
+#endif
+
+BaseType_t freertos_rs_task_local_index() {
+	return FREERTOS_RS_TASK_LOCAL_INDEX;
+}
+#endif
+
 UBaseType_t freertos_rs_get_stack_high_water_mark(TaskHandle_t task) {
 #if (INCLUDE_uxTaskGetStackHighWaterMark == 1)
 	return uxTaskGetStackHighWaterMark(task);
@@ -278,6 +412,101 @@ UBaseType_t freertos_rs_queue_messages_waiting(QueueHandle_t queue) {
 	return uxQueueMessagesWaiting( queue );
 }
 
+UBaseType_t freertos_rs_queue_messages_waiting_isr(QueueHandle_t queue) {
+	return uxQueueMessagesWaitingFromISR( queue );
+}
+
+UBaseType_t freertos_rs_queue_spaces_available(QueueHandle_t queue) {
+	return uxQueueSpacesAvailable( queue );
+}
+
+UBaseType_t freertos_rs_queue_send_to_front(QueueHandle_t queue, void* item, TickType_t max_wait) {
+	if (xQueueSendToFront(queue, item, max_wait) != pdTRUE) {
+		return 1;
+	}
+	return 0;
+}
+
+UBaseType_t freertos_rs_queue_send_to_front_isr(QueueHandle_t queue, void* item, BaseType_t* xHigherPriorityTaskWoken) {
+	if (xQueueSendToFrontFromISR(queue, item, xHigherPriorityTaskWoken) != pdTRUE) {
+		return 1;
+	}
+	return 0;
+}
+
+void freertos_rs_queue_overwrite(QueueHandle_t queue, void* item) {
+	xQueueOverwrite(queue, item);
+}
+
+void freertos_rs_queue_overwrite_isr(QueueHandle_t queue, void* item, BaseType_t* xHigherPriorityTaskWoken) {
+	xQueueOverwriteFromISR(queue, item, xHigherPriorityTaskWoken);
+}
+
+UBaseType_t freertos_rs_queue_receive_isr(QueueHandle_t queue, void* item, BaseType_t* xHigherPriorityTaskWoken) {
+	if (xQueueReceiveFromISR(queue, item, xHigherPriorityTaskWoken) != pdTRUE) {
+		return 1;
+	}
+	return 0;
+}
+
+UBaseType_t freertos_rs_queue_peek(QueueHandle_t queue, void* item, TickType_t max_wait) {
+	if (xQueuePeek(queue, item, max_wait) != pdTRUE) {
+		return 1;
+	}
+	return 0;
+}
+
+UBaseType_t freertos_rs_queue_peek_isr(QueueHandle_t queue, void* item) {
+	if (xQueuePeekFromISR(queue, item) != pdTRUE) {
+		return 1;
+	}
+	return 0;
+}
+
+void freertos_rs_queue_reset(QueueHandle_t queue) {
+	xQueueReset(queue);
+}
+
+UBaseType_t freertos_rs_queue_is_full_isr(QueueHandle_t queue) {
+	return xQueueIsQueueFullFromISR(queue) != pdFALSE ? 1 : 0;
+}
+
+UBaseType_t freertos_rs_queue_is_empty_isr(QueueHandle_t queue) {
+	return xQueueIsQueueEmptyFromISR(queue) != pdFALSE ? 1 : 0;
+}
+
+#if defined(FREERTOS_RS_FEATURE_QUEUE_SET) && (configUSE_QUEUE_SETS != 1)
+#error "The queue_set feature requires configUSE_QUEUE_SETS"
+#endif
+
+#if (configUSE_QUEUE_SETS == 1)
+QueueSetHandle_t freertos_rs_queue_set_create(UBaseType_t event_queue_length) {
+	return xQueueCreateSet(event_queue_length);
+}
+
+UBaseType_t freertos_rs_queue_set_add(QueueSetMemberHandle_t member, QueueSetHandle_t queue_set) {
+	if (xQueueAddToSet(member, queue_set) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+UBaseType_t freertos_rs_queue_set_remove(QueueSetMemberHandle_t member, QueueSetHandle_t queue_set) {
+	if (xQueueRemoveFromSet(member, queue_set) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+QueueSetMemberHandle_t freertos_rs_queue_set_select(QueueSetHandle_t queue_set, TickType_t max_wait) {
+	return xQueueSelectFromSet(queue_set, max_wait);
+}
+
+QueueSetMemberHandle_t freertos_rs_queue_set_select_isr(QueueSetHandle_t queue_set) {
+	return xQueueSelectFromSetFromISR(queue_set);
+}
+#endif // configUSE_QUEUE_SETS
+
 void freertos_rs_isr_yield(BaseType_t xHigherPriorityTaskWoken) {
 	portYIELD_FROM_ISR(xHigherPriorityTaskWoken);
 }
@@ -338,6 +567,98 @@ BaseType_t freertos_rs_task_notify_isr(void* task, uint32_t value, uint8_t actio
 	return 0;
 }
 
+BaseType_t freertos_rs_task_notify_and_query(void* task, uint32_t value, uint8_t action, uint32_t* previous_value) {
+	eNotifyAction eAction = freertos_rs_task_notify_action(action);
+
+	BaseType_t v = xTaskNotifyAndQuery(task, value, eAction, previous_value);
+	if (v != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+BaseType_t freertos_rs_task_notify_and_query_isr(void* task, uint32_t value, uint8_t action, uint32_t* previous_value, BaseType_t* xHigherPriorityTaskWoken) {
+	eNotifyAction eAction = freertos_rs_task_notify_action(action);
+
+	BaseType_t v = xTaskNotifyAndQueryFromISR(task, value, eAction, previous_value, xHigherPriorityTaskWoken);
+	if (v != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+UBaseType_t freertos_rs_task_notify_state_clear(TaskHandle_t task) {
+	if (xTaskNotifyStateClear(task) != pdTRUE) {
+		return 1;
+	}
+	return 0;
+}
+
+#ifdef configTASK_NOTIFICATION_ARRAY_ENTRIES
+uint32_t freertos_rs_task_notify_take_indexed(UBaseType_t index, uint8_t clear_count, TickType_t wait) {
+	return ulTaskNotifyTakeIndexed(index, clear_count == 1 ? pdTRUE : pdFALSE, wait);
+}
+
+BaseType_t freertos_rs_task_notify_wait_indexed(UBaseType_t index, uint32_t ulBitsToClearOnEntry, uint32_t ulBitsToClearOnExit, uint32_t *pulNotificationValue, TickType_t xTicksToWait) {
+	if (xTaskNotifyWaitIndexed(index, ulBitsToClearOnEntry, ulBitsToClearOnExit, pulNotificationValue, xTicksToWait) == pdTRUE) {
+		return 0;
+	}
+
+	return 1;
+}
+
+BaseType_t freertos_rs_task_notify_indexed(void* task, UBaseType_t index, uint32_t value, uint8_t action) {
+	eNotifyAction eAction = freertos_rs_task_notify_action(action);
+
+	BaseType_t v = xTaskNotifyIndexed(task, index, value, eAction);
+	if (v != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+BaseType_t freertos_rs_task_notify_indexed_isr(void* task, UBaseType_t index, uint32_t value, uint8_t action, BaseType_t* xHigherPriorityTaskWoken) {
+	eNotifyAction eAction = freertos_rs_task_notify_action(action);
+
+	BaseType_t v = xTaskNotifyIndexedFromISR(task, index, value, eAction, xHigherPriorityTaskWoken);
+	if (v != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+BaseType_t freertos_rs_task_notify_and_query_indexed(void* task, UBaseType_t index, uint32_t value, uint8_t action, uint32_t* previous_value) {
+	eNotifyAction eAction = freertos_rs_task_notify_action(action);
+
+	BaseType_t v = xTaskNotifyAndQueryIndexed(task, index, value, eAction, previous_value);
+	if (v != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+BaseType_t freertos_rs_task_notify_and_query_indexed_isr(void* task, UBaseType_t index, uint32_t value, uint8_t action, uint32_t* previous_value, BaseType_t* xHigherPriorityTaskWoken) {
+	eNotifyAction eAction = freertos_rs_task_notify_action(action);
+
+	BaseType_t v = xTaskNotifyAndQueryIndexedFromISR(task, index, value, eAction, previous_value, xHigherPriorityTaskWoken);
+	if (v != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+UBaseType_t freertos_rs_task_notify_state_clear_indexed(TaskHandle_t task, UBaseType_t index) {
+	if (xTaskNotifyStateClearIndexed(task, index) != pdTRUE) {
+		return 1;
+	}
+	return 0;
+}
+
+uint32_t freertos_rs_task_notify_value_clear_indexed(TaskHandle_t task, UBaseType_t index, uint32_t bits_to_clear) {
+	return ulTaskNotifyValueClearIndexed(task, index, bits_to_clear);
+}
+#endif
+
 #if ( ( INCLUDE_xTaskGetCurrentTaskHandle == 1 ) || ( configUSE_MUTEXES == 1 ) )
 TaskHandle_t freertos_rs_get_current_task() {
 	return xTaskGetCurrentTaskHandle();
@@ -364,24 +685,16 @@ void freertos_rs_vTaskSetTaskNumber(TaskHandle_t task, const UBaseType_t value)
 
 #if (configUSE_TIMERS == 1)
 
-TimerHandle_t freertos_rs_timer_create(const char * const name, uint8_t name_len, const TickType_t period,
+// FreeRTOS keeps a pointer to the name, so it must stay valid as long as the timer exists.
+TimerHandle_t freertos_rs_timer_create(const char * const name, const TickType_t period,
 		uint8_t auto_reload, void * const timer_id, TimerCallbackFunction_t callback)
 {
-	char c_name[configMAX_TASK_NAME_LEN] = {0};
-	for (int i = 0; i < name_len; i++) {
-		c_name[i] = name[i];
-
-		if (i == configMAX_TASK_NAME_LEN - 1) {
-			break;
-		}
-	}
-
 	UBaseType_t timer_auto_reload = pdFALSE;
 	if (auto_reload == 1) {
 		timer_auto_reload = pdTRUE;
 	}
 
-	TimerHandle_t handle = xTimerCreate(c_name, period, timer_auto_reload, timer_id, callback);
+	TimerHandle_t handle = xTimerCreate(name, period, timer_auto_reload, timer_id, callback);
 	return handle;
 }
 
@@ -420,10 +733,79 @@ BaseType_t freertos_rs_timer_change_period(TimerHandle_t timer, TickType_t block
 	return 0;
 }
 
+BaseType_t freertos_rs_timer_stop_from_isr(TimerHandle_t timer, BaseType_t* xHigherPriorityTaskWoken) {
+	if (xTimerStopFromISR(timer, xHigherPriorityTaskWoken) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+BaseType_t freertos_rs_timer_change_period_from_isr(TimerHandle_t timer, TickType_t new_period, BaseType_t* xHigherPriorityTaskWoken) {
+	if (xTimerChangePeriodFromISR(timer, new_period, xHigherPriorityTaskWoken) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+BaseType_t freertos_rs_timer_reset(TimerHandle_t timer, TickType_t block_time) {
+	if (xTimerReset(timer, block_time) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+BaseType_t freertos_rs_timer_reset_from_isr(TimerHandle_t timer, BaseType_t* xHigherPriorityTaskWoken) {
+	if (xTimerResetFromISR(timer, xHigherPriorityTaskWoken) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+BaseType_t freertos_rs_timer_is_active(TimerHandle_t timer) {
+	return xTimerIsTimerActive(timer) != pdFALSE ? 1 : 0;
+}
+
+TickType_t freertos_rs_timer_get_period(TimerHandle_t timer) {
+	return xTimerGetPeriod(timer);
+}
+
+TickType_t freertos_rs_timer_get_expiry_time(TimerHandle_t timer) {
+	return xTimerGetExpiryTime(timer);
+}
+
+void freertos_rs_timer_set_reload_mode(TimerHandle_t timer, uint8_t auto_reload) {
+	vTimerSetReloadMode(timer, auto_reload == 1 ? pdTRUE : pdFALSE);
+}
+
+const char* freertos_rs_timer_get_name(TimerHandle_t timer) {
+	return pcTimerGetName(timer);
+}
+
 void* freertos_rs_timer_get_id(TimerHandle_t timer) {
 	return pvTimerGetTimerID(timer);
 }
 
+BaseType_t freertos_rs_pend_function_call(PendedFunction_t function, void* parameter1, uint32_t parameter2, TickType_t block_time) {
+#if (INCLUDE_xTimerPendFunctionCall == 1)
+	if (xTimerPendFunctionCall(function, parameter1, parameter2, block_time) != pdPASS) {
+		return 1;
+	}
+	return 0;
+#else
+	// Always available, timers fall back to leaking their callback on deletion.
+	return 1;
+#endif
+}
+
+#if (INCLUDE_xTimerPendFunctionCall == 1)
+BaseType_t freertos_rs_pend_function_call_isr(PendedFunction_t function, void* parameter1, uint32_t parameter2, BaseType_t* xHigherPriorityTaskWoken) {
+	if (xTimerPendFunctionCallFromISR(function, parameter1, parameter2, xHigherPriorityTaskWoken) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+#endif
+
 #endif
 
 void freertos_rs_enter_critical() {
@@ -434,6 +816,25 @@ void freertos_rs_exit_critical() {
 	taskEXIT_CRITICAL();
 }
 
+UBaseType_t freertos_rs_enter_critical_from_isr() {
+	return taskENTER_CRITICAL_FROM_ISR();
+}
+
+void freertos_rs_exit_critical_from_isr(UBaseType_t saved_interrupt_status) {
+	taskEXIT_CRITICAL_FROM_ISR(saved_interrupt_status);
+}
+
+// Only Cortex-M can tell, other ports always report task context.
+UBaseType_t freertos_rs_is_in_isr() {
+#if defined(__ARM_ARCH_6M__) || defined(__ARM_ARCH_7M__) || defined(__ARM_ARCH_7EM__) || defined(__ARM_ARCH_8M_BASE__) || defined(__ARM_ARCH_8M_MAIN__) || defined(__ARM_ARCH_8_1M_MAIN__)
+	uint32_t ipsr;
+	__asm volatile ("mrs %0, ipsr" : "=r" (ipsr));
+	return ipsr != 0 ? 1 : 0;
+#else
+	return 0;
+#endif
+}
+
 EventGroupHandle_t freertos_rs_event_group_create() {
 	return xEventGroupCreate();
 }
@@ -461,3 +862,177 @@ EventBits_t freertos_rs_event_group_wait_bits(const EventGroupHandle_t event_gro
 EventBits_t freertos_rs_event_group_sync(EventGroupHandle_t event_group, const EventBits_t bits_to_set, const EventBits_t bits_to_wait_for, TickType_t ticks_to_wait) {
 	return xEventGroupSync(event_group, bits_to_set, bits_to_wait_for, ticks_to_wait);
 }
+
+#if ((INCLUDE_xTimerPendFunctionCall == 1) && (configUSE_TIMERS == 1))
+BaseType_t freertos_rs_event_group_set_bits_isr(EventGroupHandle_t event_group, const EventBits_t bits_to_set, BaseType_t* xHigherPriorityTaskWoken) {
+	if (xEventGroupSetBitsFromISR(event_group, bits_to_set, xHigherPriorityTaskWoken) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+#endif
+
+StreamBufferHandle_t freertos_rs_stream_buffer_create(size_t buffer_size, size_t trigger_level) {
+	return xStreamBufferCreate(buffer_size, trigger_level);
+}
+
+void freertos_rs_stream_buffer_delete(StreamBufferHandle_t stream_buffer) {
+	vStreamBufferDelete(stream_buffer);
+}
+
+size_t freertos_rs_stream_buffer_send(StreamBufferHandle_t stream_buffer, const void* data, size_t data_len, TickType_t max_wait) {
+	return xStreamBufferSend(stream_buffer, data, data_len, max_wait);
+}
+
+size_t freertos_rs_stream_buffer_send_isr(StreamBufferHandle_t stream_buffer, const void* data, size_t data_len, BaseType_t* xHigherPriorityTaskWoken) {
+	return xStreamBufferSendFromISR(stream_buffer, data, data_len, xHigherPriorityTaskWoken);
+}
+
+size_t freertos_rs_stream_buffer_receive(StreamBufferHandle_t stream_buffer, void* buffer, size_t buffer_len, TickType_t max_wait) {
+	return xStreamBufferReceive(stream_buffer, buffer, buffer_len, max_wait);
+}
+
+size_t freertos_rs_stream_buffer_receive_isr(StreamBufferHandle_t stream_buffer, void* buffer, size_t buffer_len, BaseType_t* xHigherPriorityTaskWoken) {
+	return xStreamBufferReceiveFromISR(stream_buffer, buffer, buffer_len, xHigherPriorityTaskWoken);
+}
+
+size_t freertos_rs_stream_buffer_bytes_available(StreamBufferHandle_t stream_buffer) {
+	return xStreamBufferBytesAvailable(stream_buffer);
+}
+
+size_t freertos_rs_stream_buffer_spaces_available(StreamBufferHandle_t stream_buffer) {
+	return xStreamBufferSpacesAvailable(stream_buffer);
+}
+
+UBaseType_t freertos_rs_stream_buffer_is_empty(StreamBufferHandle_t stream_buffer) {
+	return xStreamBufferIsEmpty(stream_buffer) == pdTRUE ? 1 : 0;
+}
+
+UBaseType_t freertos_rs_stream_buffer_is_full(StreamBufferHandle_t stream_buffer) {
+	return xStreamBufferIsFull(stream_buffer) == pdTRUE ? 1 : 0;
+}
+
+UBaseType_t freertos_rs_stream_buffer_reset(StreamBufferHandle_t stream_buffer) {
+	if (xStreamBufferReset(stream_buffer) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+UBaseType_t freertos_rs_stream_buffer_set_trigger_level(StreamBufferHandle_t stream_buffer, size_t trigger_level) {
+	if (xStreamBufferSetTriggerLevel(stream_buffer, trigger_level) != pdTRUE) {
+		return 1;
+	}
+	return 0;
+}
+
+MessageBufferHandle_t freertos_rs_message_buffer_create(size_t buffer_size) {
+	return xMessageBufferCreate(buffer_size);
+}
+
+void freertos_rs_message_buffer_delete(MessageBufferHandle_t message_buffer) {
+	vMessageBufferDelete(message_buffer);
+}
+
+UBaseType_t freertos_rs_message_buffer_send(MessageBufferHandle_t message_buffer, const void* data, size_t data_len, TickType_t max_wait) {
+	if (xMessageBufferSend(message_buffer, data, data_len, max_wait) != data_len) {
+		return 1;
+	}
+	return 0;
+}
+
+UBaseType_t freertos_rs_message_buffer_send_isr(MessageBufferHandle_t message_buffer, const void* data, size_t data_len, BaseType_t* xHigherPriorityTaskWoken) {
+	if (xMessageBufferSendFromISR(message_buffer, data, data_len, xHigherPriorityTaskWoken) != data_len) {
+		return 1;
+	}
+	return 0;
+}
+
+size_t freertos_rs_message_buffer_receive(MessageBufferHandle_t message_buffer, void* buffer, size_t buffer_len, TickType_t max_wait) {
+	return xMessageBufferReceive(message_buffer, buffer, buffer_len, max_wait);
+}
+
+size_t freertos_rs_message_buffer_receive_isr(MessageBufferHandle_t message_buffer, void* buffer, size_t buffer_len, BaseType_t* xHigherPriorityTaskWoken) {
+	return xMessageBufferReceiveFromISR(message_buffer, buffer, buffer_len, xHigherPriorityTaskWoken);
+}
+
+size_t freertos_rs_message_buffer_next_length_bytes(MessageBufferHandle_t message_buffer) {
+	return xMessageBufferNextLengthBytes(message_buffer);
+}
+
+size_t freertos_rs_message_buffer_spaces_available(MessageBufferHandle_t message_buffer) {
+	return xMessageBufferSpaceAvailable(message_buffer);
+}
+
+UBaseType_t freertos_rs_message_buffer_is_empty(MessageBufferHandle_t message_buffer) {
+	return xMessageBufferIsEmpty(message_buffer) == pdTRUE ? 1 : 0;
+}
+
+UBaseType_t freertos_rs_message_buffer_is_full(MessageBufferHandle_t message_buffer) {
+	return xMessageBufferIsFull(message_buffer) == pdTRUE ? 1 : 0;
+}
+
+UBaseType_t freertos_rs_message_buffer_reset(MessageBufferHandle_t message_buffer) {
+	if (xMessageBufferReset(message_buffer) != pdPASS) {
+		return 1;
+	}
+	return 0;
+}
+
+#if defined(FREERTOS_RS_FEATURE_STATIC_ALLOC) && (configSUPPORT_STATIC_ALLOCATION != 1)
+#error "The static_alloc feature requires configSUPPORT_STATIC_ALLOCATION"
+#endif
+
+#if (configSUPPORT_STATIC_ALLOCATION == 1)
+UBaseType_t freertos_rs_spawn_task_static(TaskFunction_t entry_point, void* pvParameters, const char * const name, uint8_t name_len, uint32_t stack_size, UBaseType_t priority, StackType_t* stack_buffer, StaticTask_t* task_buffer, TaskHandle_t* task_handle) {
+	char c_name[configMAX_TASK_NAME_LEN] = {0};
+	freertos_rs_copy_task_name(c_name, name, name_len);
+
+	*task_handle = xTaskCreateStatic(entry_point, c_name, stack_size, pvParameters, priority, stack_buffer, task_buffer);
+
+	if (*task_handle == NULL) {
+		return 1;
+	}
+
+	return 0;
+}
+
+QueueHandle_t freertos_rs_queue_create_static(UBaseType_t queue_length, UBaseType_t item_size, uint8_t* storage, StaticQueue_t* queue_buffer) {
+	return xQueueCreateStatic(queue_length, item_size, storage, queue_buffer);
+}
+
+SemaphoreHandle_t freertos_rs_create_binary_semaphore_static(StaticSemaphore_t* semaphore_buffer) {
+	return xSemaphoreCreateBinaryStatic(semaphore_buffer);
+}
+
+SemaphoreHandle_t freertos_rs_create_counting_semaphore_static(UBaseType_t max, UBaseType_t initial, StaticSemaphore_t* semaphore_buffer) {
+	return xSemaphoreCreateCountingStatic(max, initial, semaphore_buffer);
+}
+
+SemaphoreHandle_t freertos_rs_create_mutex_static(StaticSemaphore_t* semaphore_buffer) {
+	return xSemaphoreCreateMutexStatic(semaphore_buffer);
+}
+
+#if (configUSE_RECURSIVE_MUTEXES == 1)
+SemaphoreHandle_t freertos_rs_create_recursive_mutex_static(StaticSemaphore_t* semaphore_buffer) {
+	return xSemaphoreCreateRecursiveMutexStatic(semaphore_buffer);
+}
+#endif
+
+EventGroupHandle_t freertos_rs_event_group_create_static(StaticEventGroup_t* event_group_buffer) {
+	return xEventGroupCreateStatic(event_group_buffer);
+}
+
+#if (configUSE_TIMERS == 1)
+TimerHandle_t freertos_rs_timer_create_static(const char * const name, const TickType_t period,
+		uint8_t auto_reload, void * const timer_id, TimerCallbackFunction_t callback, StaticTimer_t* timer_buffer)
+{
+	UBaseType_t timer_auto_reload = pdFALSE;
+	if (auto_reload == 1) {
+		timer_auto_reload = pdTRUE;
+	}
+
+	return xTimerCreateStatic(name, period, timer_auto_reload, timer_id, callback, timer_buffer);
+}
+#endif // configUSE_TIMERS
+#endif // configSUPPORT_STATIC_ALLOCATION
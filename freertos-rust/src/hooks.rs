@@ -2,11 +2,31 @@ use crate::base::*;
 use crate::utils::*;
 
 use core::cell::OnceCell;
+#[cfg(feature = "hook_stack_overflow")]
+use core::ffi::CStr;
 
 type Callback = fn();
+#[cfg(feature = "hook_stack_overflow")]
+type StackOverflowCallback = fn(FreeRtosTaskHandle, &str);
 
+/// Rust callbacks for the FreeRTOS application hooks.
+///
+/// The C hook functions are only exported with their cargo feature (`hook_idle`,
+/// `hook_tick`, `hook_stack_overflow`, `hook_malloc_failed` and
+/// `hook_daemon_task_startup`), so the application can still define them in C. The
+/// matching `configUSE_*_HOOK` option must be enabled as well.
 pub struct FreeRtosHooks {
     on_assert: OnceCell<Callback>,
+    #[cfg(feature = "hook_idle")]
+    on_idle: OnceCell<Callback>,
+    #[cfg(feature = "hook_tick")]
+    on_tick: OnceCell<Callback>,
+    #[cfg(feature = "hook_stack_overflow")]
+    on_stack_overflow: OnceCell<StackOverflowCallback>,
+    #[cfg(feature = "hook_malloc_failed")]
+    on_malloc_failed: OnceCell<Callback>,
+    #[cfg(feature = "hook_daemon_task_startup")]
+    on_daemon_task_startup: OnceCell<Callback>,
 }
 
 impl FreeRtosHooks {
@@ -14,6 +34,42 @@ impl FreeRtosHooks {
         self.on_assert.set(c)
     }
 
+    /// Called on every iteration of the idle task. Must never block.
+    #[cfg(feature = "hook_idle")]
+    pub fn set_on_idle(&self, c: Callback) -> Result<(), Callback> {
+        self.on_idle.set(c)
+    }
+
+    /// Called from the tick interrupt, so only interrupt safe functions may be used.
+    #[cfg(feature = "hook_tick")]
+    pub fn set_on_tick(&self, c: Callback) -> Result<(), Callback> {
+        self.on_tick.set(c)
+    }
+
+    /// Called with the handle and name of the offending task when a stack overflow is
+    /// detected. Requires `configCHECK_FOR_STACK_OVERFLOW`.
+    ///
+    /// The stack of the task is corrupted, so the callback should not return; it can
+    /// reset the system or suspend the task, for example. If it returns, or no callback
+    /// is set, the hook loops forever.
+    #[cfg(feature = "hook_stack_overflow")]
+    pub fn set_on_stack_overflow(&self, c: StackOverflowCallback) -> Result<(), StackOverflowCallback> {
+        self.on_stack_overflow.set(c)
+    }
+
+    /// Called when the FreeRTOS heap is exhausted. The failed allocation returns null
+    /// afterwards.
+    #[cfg(feature = "hook_malloc_failed")]
+    pub fn set_on_malloc_failed(&self, c: Callback) -> Result<(), Callback> {
+        self.on_malloc_failed.set(c)
+    }
+
+    /// Called once by the timer daemon task when it starts, after the scheduler started.
+    #[cfg(feature = "hook_daemon_task_startup")]
+    pub fn set_on_daemon_task_startup(&self, c: Callback) -> Result<(), Callback> {
+        self.on_daemon_task_startup.set(c)
+    }
+
     fn do_on_assert(&self) {
         if let Some(cb) = self.on_assert.get() {
             cb()
@@ -21,12 +77,22 @@ impl FreeRtosHooks {
     }
 }
 
-// SAFETY: must only be set before the scheduler starts and accessed after the
-// kernel has asserted, both being single threaded situations.
+// SAFETY: must only be set before the scheduler starts. Afterwards the callbacks are
+// only read, which is fine from any task or interrupt.
 unsafe impl Sync for FreeRtosHooks {}
 
 pub static FREERTOS_HOOKS: FreeRtosHooks = FreeRtosHooks {
     on_assert: OnceCell::new(),
+    #[cfg(feature = "hook_idle")]
+    on_idle: OnceCell::new(),
+    #[cfg(feature = "hook_tick")]
+    on_tick: OnceCell::new(),
+    #[cfg(feature = "hook_stack_overflow")]
+    on_stack_overflow: OnceCell::new(),
+    #[cfg(feature = "hook_malloc_failed")]
+    on_malloc_failed: OnceCell::new(),
+    #[cfg(feature = "hook_daemon_task_startup")]
+    on_daemon_task_startup: OnceCell::new(),
 };
 
 #[allow(unused_doc_comments)]
@@ -42,3 +108,58 @@ pub extern "C" fn vAssertCalled(file_name_ptr: FreeRtosCharPtr, line: FreeRtosUB
     panic!("FreeRTOS ASSERT: {}:{}", file_name, line);
     //loop {}
 }
+
+#[cfg(feature = "hook_idle")]
+#[no_mangle]
+pub extern "C" fn vApplicationIdleHook() {
+    if let Some(cb) = FREERTOS_HOOKS.on_idle.get() {
+        cb()
+    }
+}
+
+#[cfg(feature = "hook_tick")]
+#[no_mangle]
+pub extern "C" fn vApplicationTickHook() {
+    if let Some(cb) = FREERTOS_HOOKS.on_tick.get() {
+        cb()
+    }
+}
+
+/// # Safety
+///
+/// Only called by FreeRTOS, `task_name_ptr` points to the nul-terminated name of `task`.
+#[cfg(feature = "hook_stack_overflow")]
+#[no_mangle]
+pub unsafe extern "C" fn vApplicationStackOverflowHook(
+    task: FreeRtosTaskHandle,
+    task_name_ptr: FreeRtosCharPtr,
+) {
+    let task_name = unsafe { CStr::from_ptr(task_name_ptr as *const _) }
+        .to_str()
+        .unwrap_or("");
+
+    if let Some(cb) = FREERTOS_HOOKS.on_stack_overflow.get() {
+        cb(task, task_name)
+    }
+
+    // The stack of the task is corrupted, it can't continue.
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+#[cfg(feature = "hook_malloc_failed")]
+#[no_mangle]
+pub extern "C" fn vApplicationMallocFailedHook() {
+    if let Some(cb) = FREERTOS_HOOKS.on_malloc_failed.get() {
+        cb()
+    }
+}
+
+#[cfg(feature = "hook_daemon_task_startup")]
+#[no_mangle]
+pub extern "C" fn vApplicationDaemonTaskStartupHook() {
+    if let Some(cb) = FREERTOS_HOOKS.on_daemon_task_startup.get() {
+        cb()
+    }
+}
@@ -0,0 +1,145 @@
+use crate::base::*;
+use crate::prelude::v1::*;
+use crate::semaphore::*;
+use crate::task::*;
+use crate::units::*;
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Shared between a spawned task and its join handle.
+pub(crate) struct Packet<R> {
+    /// `None` once finished means the task panicked.
+    result: UnsafeCell<Option<R>>,
+    finished: AtomicBool,
+    done: Semaphore,
+}
+
+unsafe impl<R: Send> Send for Packet<R> {}
+unsafe impl<R: Send> Sync for Packet<R> {}
+
+impl<R> Packet<R> {
+    pub(crate) fn new() -> Result<Packet<R>, FreeRtosError> {
+        Ok(Packet {
+            result: UnsafeCell::new(None),
+            finished: AtomicBool::new(false),
+            done: Semaphore::new_binary()?,
+        })
+    }
+
+    /// Run `func` on the spawned task and publish its result. Returns `false` if
+    /// `func` panicked.
+    pub(crate) fn run<F: FnOnce(Task) -> R>(&self, this_task: Task, func: F) -> bool {
+        #[cfg(feature = "std")]
+        let result = std::panic::catch_unwind(core::panic::AssertUnwindSafe(|| func(this_task))).ok();
+        #[cfg(not(feature = "std"))]
+        let result = Some(func(this_task));
+
+        let returned = result.is_some();
+
+        // The joining side only reads the result after `finished` is set.
+        unsafe {
+            *self.result.get() = result;
+        }
+        self.finished.store(true, Ordering::Release);
+        self.done.give();
+        returned
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// Wait for the result. Returns `None` on timeout and `Some(None)` if the task panicked.
+    pub(crate) fn join<D: DurationTicks>(&self, max_wait: D) -> Option<Option<R>> {
+        if self.done.take(max_wait).is_err() {
+            return None;
+        }
+
+        // The task gave the semaphore after storing its result and never touches it again.
+        Some(unsafe { (*self.result.get()).take() })
+    }
+}
+
+/// An owned permission to wait for a task to finish and take its return value.
+/// Created with [`TaskBuilder::spawn`].
+///
+/// Dropping the handle detaches the task.
+///
+/// Sample usage:
+///
+/// ```rust
+/// # use freertos_rs::*;
+/// let handle = Task::new().name("compute").spawn(|_this_task| {
+///     CurrentTask::delay(Duration::ms(100));
+///     42
+/// }).unwrap();
+///
+/// let result = handle.join(Duration::ms(1000)).unwrap();
+/// # println!("{}", result);
+/// ```
+pub struct JoinHandle<R> {
+    task: Task,
+    packet: Arc<Packet<R>>,
+}
+
+/// Error returned by [`JoinHandle::join`].
+#[derive(Debug)]
+pub enum JoinError<R> {
+    /// The task did not finish in time. The handle is given back to wait again.
+    Timeout(JoinHandle<R>),
+    /// The task panicked. Only reported with the `std` feature, where panics
+    /// unwind; otherwise a panicking task never finishes.
+    Panicked,
+}
+
+impl TaskBuilder {
+    /// Start a new task and return a handle to wait for its return value.
+    pub fn spawn<F, R>(&self, func: F) -> Result<JoinHandle<R>, FreeRtosError>
+    where
+        F: FnOnce(Task) -> R,
+        F: Send + 'static,
+        R: Send + 'static,
+    {
+        let packet = Arc::new(Packet::new()?);
+
+        let their_packet = packet.clone();
+        let task = self.start(move |this_task| {
+            their_packet.run(this_task, func);
+        })?;
+
+        Ok(JoinHandle { task, packet })
+    }
+}
+
+impl<R> JoinHandle<R> {
+    /// Get the handle of the spawned task.
+    pub fn task(&self) -> &Task {
+        &self.task
+    }
+
+    /// Check whether the task has finished, without blocking.
+    pub fn is_finished(&self) -> bool {
+        self.packet.is_finished()
+    }
+
+    /// Wait for the task to finish and take its return value.
+    ///
+    /// A panic of the task is only detected with the `std` feature. Without it, panics
+    /// don't unwind, so a task that panicked never finishes and `join` times out.
+    pub fn join<D: DurationTicks>(self, max_wait: D) -> Result<R, JoinError<R>> {
+        match self.packet.join(max_wait) {
+            Some(Some(r)) => Ok(r),
+            Some(None) => Err(JoinError::Panicked),
+            None => Err(JoinError::Timeout(self)),
+        }
+    }
+}
+
+impl<R> fmt::Debug for JoinHandle<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("task", &self.task)
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
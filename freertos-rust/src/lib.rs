@@ -2,14 +2,24 @@
 //!
 //! Rust interface for the FreeRTOS embedded operating system.
 //! It is assumed that dynamic memory allocation is provided on the target system.
+//! Without the `alloc` feature the crate does not use the `alloc` crate at all; tasks
+//! are then started from plain functions and task and timer names are stored inline.
 //! For Rust versions 1.68 and later, the stable channel can be used.
 //! Prior to version 1.68, the nightly channel is required along with
 //! enabling the `alloc_error_handler` feature.
+//! The `executor` feature requires Rust 1.83 or later.
 //!
 //! This library interfaces with FreeRTOS using a C shim library which provides function
 //! wrappers for FreeRTOS macros. The compiled Rust application should be linked to the
 //! base C/C++ firmware binary.
 //!
+//! The `critical-section` feature registers an implementation of the
+//! [critical-section](https://crates.io/crates/critical-section) crate, as required by
+//! crates like `heapless` or `defmt`. It uses the critical regions of the kernel, which
+//! only mask interrupts up to `configMAX_SYSCALL_INTERRUPT_PRIORITY`, so interrupts of a
+//! higher priority still run and must not use the critical section. Interrupt context is
+//! detected on Cortex-M, on other ports it is always entered like from a task.
+//!
 //! Examples are provided inside [freertos-rust-examples](https://github.com/lobaro/FreeRTOS-rust/tree/master/freertos-rust-examples)
 //!
 //! For more examples, check the enclosed GCC ARM/Rust/QEMU based unit tests. The project
@@ -60,8 +70,11 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
+#[cfg(feature = "alloc")]
 #[cfg_attr(any(feature = "time", feature = "sync"), macro_use)]
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 #[cfg(feature = "hooks")]
 mod hooks;
@@ -72,6 +85,8 @@ mod shim;
 mod allocator;
 mod base;
 #[cfg(feature = "sync")]
+mod condvar;
+#[cfg(feature = "sync")]
 mod critical;
 #[cfg(feature = "time")]
 mod delays;
@@ -81,20 +96,40 @@ mod isr;
 mod mutex;
 #[cfg(feature = "sync")]
 mod queue;
+#[cfg(feature = "queue_set")]
+mod queue_set;
 #[cfg(feature = "sync")]
 mod semaphore;
 #[cfg(feature = "sync")]
 mod event_group;
+#[cfg(all(feature = "sync", feature = "alloc"))]
+mod join_handle;
+#[cfg(all(feature = "sync", feature = "alloc"))]
+mod scope;
+#[cfg(feature = "static_alloc")]
+mod static_alloc;
+#[cfg(feature = "stream_buffer")]
+mod stream_buffer;
+#[cfg(feature = "message_buffer")]
+mod message_buffer;
+#[cfg(any(feature = "time", feature = "sync"))]
+mod notify;
 #[cfg(any(feature = "time", feature = "sync"))]
 mod task;
+#[cfg(feature = "task_local")]
+mod task_local;
 #[cfg(feature = "time")]
 mod timers;
+#[cfg(feature = "executor")]
+mod waker;
 #[cfg(any(feature = "time", feature = "sync"))]
 mod units;
 mod utils;
 
-#[cfg(feature = "sync")]
+#[cfg(all(feature = "sync", feature = "alloc"))]
 pub mod patterns;
+#[cfg(feature = "executor")]
+pub mod executor;
 
 // Internal stuff that is only public for first Proof of Concept
 pub use crate::base::*;
@@ -105,6 +140,8 @@ pub use crate::shim::*;
 pub use crate::allocator::*;
 pub use crate::base::FreeRtosError;
 #[cfg(feature = "sync")]
+pub use crate::condvar::*;
+#[cfg(feature = "sync")]
 pub use crate::critical::*;
 #[cfg(feature = "time")]
 pub use crate::delays::*;
@@ -116,12 +153,28 @@ pub use crate::isr::*;
 pub use crate::mutex::*;
 #[cfg(feature = "sync")]
 pub use crate::queue::*;
+#[cfg(feature = "queue_set")]
+pub use crate::queue_set::*;
 #[cfg(feature = "sync")]
 pub use crate::semaphore::*;
 #[cfg(feature = "sync")]
 pub use crate::event_group::*;
+#[cfg(all(feature = "sync", feature = "alloc"))]
+pub use crate::join_handle::*;
+#[cfg(all(feature = "sync", feature = "alloc"))]
+pub use crate::scope::*;
+#[cfg(feature = "static_alloc")]
+pub use crate::static_alloc::*;
+#[cfg(feature = "stream_buffer")]
+pub use crate::stream_buffer::*;
+#[cfg(feature = "message_buffer")]
+pub use crate::message_buffer::*;
+#[cfg(any(feature = "time", feature = "sync"))]
+pub use crate::notify::*;
 #[cfg(any(feature = "time", feature = "sync"))]
 pub use crate::task::*;
+#[cfg(feature = "task_local")]
+pub use crate::task_local::*;
 #[cfg(feature = "time")]
 pub use crate::timers::*;
 #[cfg(any(feature = "time", feature = "sync"))]
@@ -130,3 +183,5 @@ pub use crate::units::*;
 #[cfg(feature = "cpu_clock")]
 pub use crate::utils::cpu_clock_hz;
 pub use crate::utils::shim_sanity_check;
+#[cfg(any(feature = "time", feature = "sync"))]
+pub use crate::utils::NameBuffer;
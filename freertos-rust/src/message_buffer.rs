@@ -0,0 +1,188 @@
+use crate::base::*;
+use crate::isr::*;
+#[cfg(feature = "alloc")]
+use crate::prelude::v1::*;
+use crate::shim::*;
+use crate::units::*;
+
+unsafe impl Send for MessageBuffer {}
+unsafe impl Sync for MessageBuffer {}
+
+/// A buffer that passes variable length messages from a task or an interrupt
+/// to another task. Every message is received as a whole.
+///
+/// Like stream buffers, FreeRTOS message buffers assume there is only one writer
+/// and only one reader. Concurrent access from several writers (or readers) must
+/// be serialised by the application.
+///
+/// Each message occupies its length plus the size of a `usize` in the buffer.
+/// Zero-length messages are never delivered by FreeRTOS and must not be sent.
+pub struct MessageBuffer {
+    message_buffer: FreeRtosMessageBufferHandle,
+}
+
+impl MessageBuffer {
+    /// Create a new message buffer with a total size of `capacity` bytes.
+    pub fn new(capacity: usize) -> Result<MessageBuffer, FreeRtosError> {
+        let handle = unsafe { freertos_rs_message_buffer_create(capacity) };
+
+        if handle.is_null() {
+            return Err(FreeRtosError::OutOfMemory);
+        }
+
+        Ok(MessageBuffer {
+            message_buffer: handle,
+        })
+    }
+
+    /// # Safety
+    ///
+    /// `handle` must be a valid FreeRTOS message buffer handle (not a stream buffer).
+    #[inline]
+    pub unsafe fn from_raw_handle(handle: FreeRtosMessageBufferHandle) -> Self {
+        Self {
+            message_buffer: handle,
+        }
+    }
+    #[inline]
+    pub fn raw_handle(&self) -> FreeRtosMessageBufferHandle {
+        self.message_buffer
+    }
+
+    /// Send a message. Wait for the buffer to have enough space for all of it. Empty
+    /// messages can't be received, so they are rejected.
+    pub fn send<D: DurationTicks>(&self, message: &[u8], max_wait: D) -> Result<(), FreeRtosError> {
+        if message.is_empty() {
+            return Err(FreeRtosError::EmptyMessage);
+        }
+
+        let r = unsafe {
+            freertos_rs_message_buffer_send(
+                self.message_buffer,
+                message.as_ptr() as FreeRtosVoidPtr,
+                message.len(),
+                max_wait.to_ticks(),
+            )
+        };
+
+        if r != 0 {
+            Err(FreeRtosError::QueueSendTimeout)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Send a message, from an interrupt.
+    pub fn send_from_isr(
+        &self,
+        context: &mut InterruptContext,
+        message: &[u8],
+    ) -> Result<(), FreeRtosError> {
+        if message.is_empty() {
+            return Err(FreeRtosError::EmptyMessage);
+        }
+
+        let r = unsafe {
+            freertos_rs_message_buffer_send_isr(
+                self.message_buffer,
+                message.as_ptr() as FreeRtosVoidPtr,
+                message.len(),
+                context.get_task_field_mut(),
+            )
+        };
+
+        if r != 0 {
+            Err(FreeRtosError::QueueFull)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Wait for a message and copy it into `buffer`.
+    ///
+    /// Returns the length of the message, `0` if the wait timed out or if the
+    /// next message does not fit into `buffer`. In the latter case the message
+    /// stays in the message buffer, see [`next_length_bytes`](Self::next_length_bytes).
+    pub fn receive<D: DurationTicks>(&self, buffer: &mut [u8], max_wait: D) -> usize {
+        unsafe {
+            freertos_rs_message_buffer_receive(
+                self.message_buffer,
+                buffer.as_mut_ptr() as FreeRtosMutVoidPtr,
+                buffer.len(),
+                max_wait.to_ticks(),
+            )
+        }
+    }
+
+    /// Wait for a message and return it in a newly allocated vector.
+    #[cfg(feature = "alloc")]
+    pub fn receive_vec<D: DurationTicks>(&self, max_wait: D) -> Result<Vec<u8>, FreeRtosError> {
+        let mut len = self.next_length_bytes();
+
+        if len == 0 {
+            // A receive into an empty buffer blocks until a message arrives,
+            // but leaves that message in the message buffer.
+            self.receive(&mut [], max_wait);
+            len = self.next_length_bytes();
+
+            if len == 0 {
+                return Err(FreeRtosError::QueueReceiveTimeout);
+            }
+        }
+
+        let mut message = vec![0; len];
+        let received = self.receive(&mut message, Duration::zero());
+        message.truncate(received);
+
+        Ok(message)
+    }
+
+    /// Copy the next message into `buffer`, from an interrupt.
+    ///
+    /// Returns the length of the message, `0` if there was none or it does not
+    /// fit into `buffer`.
+    pub fn receive_from_isr(&self, context: &mut InterruptContext, buffer: &mut [u8]) -> usize {
+        unsafe {
+            freertos_rs_message_buffer_receive_isr(
+                self.message_buffer,
+                buffer.as_mut_ptr() as FreeRtosMutVoidPtr,
+                buffer.len(),
+                context.get_task_field_mut(),
+            )
+        }
+    }
+
+    /// Get the length of the next message in the buffer, `0` if the buffer is empty.
+    pub fn next_length_bytes(&self) -> usize {
+        unsafe { freertos_rs_message_buffer_next_length_bytes(self.message_buffer) }
+    }
+
+    /// Get the number of free bytes in the buffer. Note that the largest message that
+    /// can still be sent is smaller by the size of a `usize`.
+    pub fn spaces_available(&self) -> usize {
+        unsafe { freertos_rs_message_buffer_spaces_available(self.message_buffer) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        unsafe { freertos_rs_message_buffer_is_empty(self.message_buffer) == 1 }
+    }
+
+    pub fn is_full(&self) -> bool {
+        unsafe { freertos_rs_message_buffer_is_full(self.message_buffer) == 1 }
+    }
+
+    /// Discard all messages in the buffer.
+    ///
+    /// Returns `true` on success, `false` when a task is blocked on the buffer.
+    pub fn reset(&self) -> bool {
+        unsafe { freertos_rs_message_buffer_reset(self.message_buffer) == 0 }
+    }
+}
+
+impl Drop for MessageBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            freertos_rs_message_buffer_delete(self.message_buffer);
+        }
+    }
+}
@@ -1,7 +1,29 @@
 use crate::base::*;
+use crate::condvar::*;
+#[cfg(feature = "mutex_holder")]
+use crate::isr::*;
 use crate::prelude::v1::*;
 use crate::shim::*;
+#[cfg(feature = "static_alloc")]
+use crate::static_alloc::*;
+#[cfg(feature = "mutex_holder")]
+use crate::task::*;
 use crate::units::*;
+#[cfg(feature = "executor")]
+use crate::waker::*;
+
+#[cfg(feature = "executor")]
+use core::future::{poll_fn, Future};
+#[cfg(feature = "debug_mutex")]
+use core::panic::Location;
+#[cfg(feature = "debug_mutex")]
+use core::ptr;
+#[cfg(feature = "debug_mutex")]
+use core::sync::atomic::{AtomicPtr, Ordering};
+#[cfg(feature = "executor")]
+use core::pin::pin;
+#[cfg(feature = "executor")]
+use core::task::Poll;
 
 pub type Mutex<T> = MutexImpl<T, MutexNormal>;
 pub type RecursiveMutex<T> = MutexImpl<T, MutexRecursive>;
@@ -14,6 +36,11 @@ unsafe impl<T: Send, M> Sync for MutexImpl<T, M> {}
 /// the current owner of a lock can re-lock it.
 pub struct MutexImpl<T: ?Sized, M> {
     mutex: M,
+    #[cfg(feature = "executor")]
+    waker: WakerList,
+    /// Where the current holder locked the mutex, null when unlocked.
+    #[cfg(feature = "debug_mutex")]
+    lock_site: AtomicPtr<Location<'static>>,
     data: UnsafeCell<T>,
 }
 
@@ -35,14 +62,110 @@ where
         Ok(Self::from_parts(M::create()?, value))
     }
 
+    /// Create a new mutex with the given inner value in caller provided storage
+    #[cfg(feature = "static_alloc")]
+    pub fn new_static(value: T, buffer: &'static mut StaticSemaphoreBuffer) -> Result<Self, FreeRtosError> {
+        Ok(Self::from_parts(M::create_static(buffer)?, value))
+    }
+
     /// Try to obtain a lock and mutable access to our inner value
+    #[cfg_attr(feature = "debug_mutex", track_caller)]
     pub fn lock<D: DurationTicks>(&self, max_wait: D) -> Result<MutexGuard<'_, T, M>, FreeRtosError> {
         self.mutex.take(max_wait)?;
 
-        Ok(MutexGuard {
+        Ok(self.guard())
+    }
+
+    /// Obtain the lock only if it is available right now
+    #[cfg_attr(feature = "debug_mutex", track_caller)]
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T, M>> {
+        self.lock(Duration::zero()).ok()
+    }
+
+    /// Wait for the lock without blocking the task.
+    ///
+    /// The future is woken when a guard of this mutex is dropped. Any number of futures
+    /// may wait on a mutex.
+    #[cfg(feature = "executor")]
+    #[cfg_attr(feature = "debug_mutex", track_caller)]
+    pub fn lock_async(&self) -> impl Future<Output = MutexGuard<'_, T, M>> + '_ {
+        #[cfg(feature = "debug_mutex")]
+        let site = Location::caller();
+
+        async move {
+            let registration = pin!(self.waker.registration());
+            poll_fn(|cx| {
+                if self.mutex.take(Duration::zero()).is_err() {
+                    registration.as_ref().register(cx.waker());
+
+                    // An unlock before the waker was registered didn't wake anyone.
+                    if self.mutex.take(Duration::zero()).is_err() {
+                        return Poll::Pending;
+                    }
+                }
+
+                let guard = self.guard();
+                #[cfg(feature = "debug_mutex")]
+                self.lock_site.store(site as *const _ as *mut _, Ordering::Relaxed);
+                Poll::Ready(guard)
+            })
+            .await
+        }
+    }
+
+    #[cfg_attr(feature = "debug_mutex", track_caller)]
+    fn guard(&self) -> MutexGuard<'_, T, M> {
+        MutexGuard {
             __mutex: &self.mutex,
+            #[cfg(feature = "executor")]
+            __waker: &self.waker,
+            #[cfg(feature = "debug_mutex")]
+            __lock_site: &self.lock_site,
+            #[cfg(feature = "debug_mutex")]
+            __previous_site: self
+                .lock_site
+                .swap(Location::caller() as *const _ as *mut _, Ordering::Relaxed),
             __data: &self.data,
-        })
+        }
+    }
+
+    /// Get the task holding the lock, `None` if the mutex is not locked.
+    /// Requires `INCLUDE_xSemaphoreGetMutexHolder` and the `mutex_holder` feature.
+    #[cfg(feature = "mutex_holder")]
+    pub fn holder(&self) -> Option<Task> {
+        let task = unsafe { freertos_rs_get_mutex_holder(self.mutex.raw_handle()) };
+        if task.is_null() {
+            None
+        } else {
+            Some(unsafe { Task::from_raw_handle(task) })
+        }
+    }
+
+    /// Get the task holding the lock from an interrupt, `None` if the mutex is not locked.
+    #[cfg(feature = "mutex_holder")]
+    pub fn holder_from_isr(&self, _context: &mut InterruptContext) -> Option<Task> {
+        let task = unsafe { freertos_rs_get_mutex_holder_isr(self.mutex.raw_handle()) };
+        if task.is_null() {
+            None
+        } else {
+            Some(unsafe { Task::from_raw_handle(task) })
+        }
+    }
+
+    /// Check whether any task holds the lock.
+    #[cfg(feature = "mutex_holder")]
+    pub fn is_locked(&self) -> bool {
+        self.holder().is_some()
+    }
+
+    /// Get the source location where the current holder locked the mutex, `None` if
+    /// the mutex is not locked through this handle.
+    ///
+    /// Recorded by `lock`, `try_lock` and `lock_async`; calls through `#[track_caller]`
+    /// functions report the location of their caller.
+    #[cfg(feature = "debug_mutex")]
+    pub fn lock_site(&self) -> Option<&'static Location<'static>> {
+        unsafe { self.lock_site.load(Ordering::Relaxed).as_ref() }
     }
 
     /// Consume the mutex and return its inner value
@@ -63,6 +186,10 @@ where
     pub fn from_parts(mutex: M, value: T) -> Self {
         Self {
             mutex,
+            #[cfg(feature = "executor")]
+            waker: WakerList::new(),
+            #[cfg(feature = "debug_mutex")]
+            lock_site: AtomicPtr::new(ptr::null_mut()),
             data: UnsafeCell::new(value),
         }
     }
@@ -84,6 +211,13 @@ where
     M: MutexInnerImpl,
 {
     __mutex: &'a M,
+    #[cfg(feature = "executor")]
+    __waker: &'a WakerList,
+    #[cfg(feature = "debug_mutex")]
+    __lock_site: &'a AtomicPtr<Location<'static>>,
+    /// Restored on unlock, set when a recursive mutex was locked again.
+    #[cfg(feature = "debug_mutex")]
+    __previous_site: *mut Location<'static>,
     __data: &'a UnsafeCell<T>,
 }
 
@@ -107,12 +241,217 @@ where
     }
 }
 
+impl<'a, T: ?Sized, M> MutexGuard<'a, T, M>
+where
+    M: MutexInnerImpl,
+{
+    /// Unlock the mutex while running `f`, then wait until it is locked again.
+    pub(crate) fn unlocked<R>(self, f: impl FnOnce() -> R) -> (Self, R) {
+        let mutex = self.__mutex;
+        #[cfg(feature = "executor")]
+        let waker = self.__waker;
+        #[cfg(feature = "debug_mutex")]
+        let (lock_site, site, previous_site) = (
+            self.__lock_site,
+            self.__lock_site.load(Ordering::Relaxed),
+            self.__previous_site,
+        );
+        let data = self.__data;
+        drop(self);
+
+        let result = f();
+
+        while mutex.take(Duration::infinite()).is_err() {}
+        #[cfg(feature = "debug_mutex")]
+        lock_site.store(site, Ordering::Relaxed);
+
+        let guard = MutexGuard {
+            __mutex: mutex,
+            #[cfg(feature = "executor")]
+            __waker: waker,
+            #[cfg(feature = "debug_mutex")]
+            __lock_site: lock_site,
+            #[cfg(feature = "debug_mutex")]
+            __previous_site: previous_site,
+            __data: data,
+        };
+        (guard, result)
+    }
+}
+
 impl<'a, T: ?Sized, M> Drop for MutexGuard<'a, T, M>
 where
     M: MutexInnerImpl,
 {
     fn drop(&mut self) {
+        #[cfg(feature = "debug_mutex")]
+        self.__lock_site.store(self.__previous_site, Ordering::Relaxed);
+
         self.__mutex.give();
+
+        #[cfg(feature = "executor")]
+        self.__waker.wake();
+    }
+}
+
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+
+#[derive(Debug, Default)]
+struct RwLockState {
+    readers: u32,
+    writer: bool,
+    writers_waiting: u32,
+}
+
+/// A reader-writer lock. Any number of tasks can hold a read lock at the same time,
+/// while a write lock is exclusive.
+///
+/// Waiting writers take precedence: no new read lock is granted while a task waits
+/// for the write lock, so frequent readers can't starve writers.
+///
+/// Sample usage:
+///
+/// ```rust
+/// # use freertos_rs::*;
+/// let config = RwLock::new(0u32).unwrap();
+/// {
+///     let value = config.read(Duration::ms(10)).unwrap();
+/// }
+/// *config.write(Duration::infinite()).unwrap() = 42;
+/// ```
+pub struct RwLock<T: ?Sized> {
+    state: Mutex<RwLockState>,
+    changed: Condvar,
+    data: UnsafeCell<T>,
+}
+
+impl<T> RwLock<T> {
+    /// Create a new reader-writer lock with the given inner value
+    pub fn new(value: T) -> Result<Self, FreeRtosError> {
+        Ok(RwLock {
+            state: Mutex::new(RwLockState::default())?,
+            changed: Condvar::new()?,
+            data: UnsafeCell::new(value),
+        })
+    }
+
+    /// Consume the lock and return its inner value
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Wait until no task holds or waits for the write lock, then obtain shared access
+    /// to our inner value.
+    pub fn read<D: DurationTicks>(&self, max_wait: D) -> Result<RwLockReadGuard<'_, T>, FreeRtosError> {
+        let state = self.lock_state();
+        let (mut state, result) = self
+            .changed
+            .wait_while(state, max_wait, |s| s.writer || s.writers_waiting > 0);
+
+        if result.timed_out() {
+            return Err(FreeRtosError::MutexTimeout);
+        }
+
+        state.readers += 1;
+        Ok(RwLockReadGuard { __lock: self })
+    }
+
+    /// Wait until no other task holds the lock, then obtain exclusive access to our
+    /// inner value.
+    pub fn write<D: DurationTicks>(&self, max_wait: D) -> Result<RwLockWriteGuard<'_, T>, FreeRtosError> {
+        let mut state = self.lock_state();
+        state.writers_waiting += 1;
+
+        let (mut state, result) = self
+            .changed
+            .wait_while(state, max_wait, |s| s.writer || s.readers > 0);
+        state.writers_waiting -= 1;
+
+        if result.timed_out() {
+            // Readers held back by this writer may continue.
+            drop(state);
+            self.changed.notify_all();
+            return Err(FreeRtosError::MutexTimeout);
+        }
+
+        state.writer = true;
+        Ok(RwLockWriteGuard { __lock: self })
+    }
+
+    /// Get mutable reference to inner value.
+    ///
+    /// This method does not lock because mutable reference guarantees exclusive access.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, RwLockState, MutexNormal> {
+        loop {
+            if let Ok(state) = self.state.lock(Duration::infinite()) {
+                return state;
+            }
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RwLock state: {:?}", self.state)
+    }
+}
+
+/// Holds a read lock until we are dropped
+pub struct RwLockReadGuard<'a, T: ?Sized + 'a> {
+    __lock: &'a RwLock<T>,
+}
+
+impl<'a, T: ?Sized> Deref for RwLockReadGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.__lock.data.get() }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for RwLockReadGuard<'a, T> {
+    fn drop(&mut self) {
+        let mut state = self.__lock.lock_state();
+        state.readers -= 1;
+
+        if state.readers == 0 {
+            drop(state);
+            self.__lock.changed.notify_all();
+        }
+    }
+}
+
+/// Holds the write lock until we are dropped
+pub struct RwLockWriteGuard<'a, T: ?Sized + 'a> {
+    __lock: &'a RwLock<T>,
+}
+
+impl<'a, T: ?Sized> Deref for RwLockWriteGuard<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.__lock.data.get() }
+    }
+}
+
+impl<'a, T: ?Sized> DerefMut for RwLockWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.__lock.data.get() }
+    }
+}
+
+impl<'a, T: ?Sized> Drop for RwLockWriteGuard<'a, T> {
+    fn drop(&mut self) {
+        self.__lock.lock_state().writer = false;
+        self.__lock.changed.notify_all();
     }
 }
 
@@ -121,6 +460,8 @@ where
     Self: Sized,
 {
     fn create() -> Result<Self, FreeRtosError>;
+    #[cfg(feature = "static_alloc")]
+    fn create_static(buffer: &'static mut StaticSemaphoreBuffer) -> Result<Self, FreeRtosError>;
     fn take<D: DurationTicks>(&self, max_wait: D) -> Result<(), FreeRtosError>;
     fn give(&self);
 
@@ -145,6 +486,15 @@ impl MutexInnerImpl for MutexNormal {
         Ok(MutexNormal(m))
     }
 
+    #[cfg(feature = "static_alloc")]
+    fn create_static(buffer: &'static mut StaticSemaphoreBuffer) -> Result<Self, FreeRtosError> {
+        let m = unsafe { freertos_rs_create_mutex_static(buffer.as_mut_ptr()?) };
+        if m == 0 as *const _ {
+            return Err(FreeRtosError::OutOfMemory);
+        }
+        Ok(MutexNormal(m))
+    }
+
     fn take<D: DurationTicks>(&self, max_wait: D) -> Result<(), FreeRtosError> {
         let res = unsafe { freertos_rs_take_semaphore(self.0, max_wait.to_ticks()) };
 
@@ -195,6 +545,15 @@ impl MutexInnerImpl for MutexRecursive {
         Ok(MutexRecursive(m))
     }
 
+    #[cfg(feature = "static_alloc")]
+    fn create_static(buffer: &'static mut StaticSemaphoreBuffer) -> Result<Self, FreeRtosError> {
+        let m = unsafe { freertos_rs_create_recursive_mutex_static(buffer.as_mut_ptr()?) };
+        if m == 0 as *const _ {
+            return Err(FreeRtosError::OutOfMemory);
+        }
+        Ok(MutexRecursive(m))
+    }
+
     fn take<D: DurationTicks>(&self, max_wait: D) -> Result<(), FreeRtosError> {
         let res = unsafe { freertos_rs_take_recursive_semaphore(self.0, max_wait.to_ticks()) };
 
@@ -0,0 +1,198 @@
+use crate::base::*;
+use crate::isr::*;
+use crate::task::*;
+use crate::units::*;
+
+// Synchronisation primitives built on direct to task notifications. Each one is bound to
+// a single receiving task and one of its notification values. Index 0 works with every
+// FreeRTOS version, other indices require FreeRTOS 10.4 or later and
+// `configTASK_NOTIFICATION_ARRAY_ENTRIES` to be large enough.
+
+unsafe impl Send for NotifySignal {}
+unsafe impl Sync for NotifySignal {}
+unsafe impl Send for NotifyCounter {}
+unsafe impl Sync for NotifyCounter {}
+unsafe impl Send for EventBits {}
+unsafe impl Sync for EventBits {}
+
+fn notify(receiver: &Task, index: u32, notification: TaskNotification) {
+    if index == 0 {
+        receiver.notify(notification);
+    } else {
+        // Only used with actions that can't fail.
+        let _ = receiver.notify_indexed(index, notification);
+    }
+}
+
+fn notify_from_isr(
+    receiver: &Task,
+    context: &mut InterruptContext,
+    index: u32,
+    notification: TaskNotification,
+) {
+    let _ = if index == 0 {
+        receiver.notify_from_isr(context, notification)
+    } else {
+        receiver.notify_indexed_from_isr(context, index, notification)
+    };
+}
+
+fn take<D: DurationTicks>(receiver: &Task, index: u32, clear: bool, wait_for: D) -> u32 {
+    debug_assert!(
+        is_current(receiver),
+        "only the receiving task may wait for its notifications"
+    );
+
+    if index == 0 {
+        CurrentTask::take_notification(clear, wait_for)
+    } else {
+        CurrentTask::take_notification_indexed(index, clear, wait_for)
+    }
+}
+
+fn is_current(receiver: &Task) -> bool {
+    Task::current()
+        .map(|t| t.raw_handle() == receiver.raw_handle())
+        .unwrap_or(false)
+}
+
+/// A binary semaphore that can only be taken by one task.
+///
+/// Sample usage:
+///
+/// ```rust
+/// # use freertos_rs::*;
+/// let rx_done = NotifySignal::new(Task::current().unwrap(), 0);
+///
+/// // in the interrupt handler
+/// # let mut context = InterruptContext::new();
+/// rx_done.signal_from_isr(&mut context);
+///
+/// // in the driver task
+/// if rx_done.wait(Duration::ms(10)) {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct NotifySignal {
+    receiver: Task,
+    index: u32,
+}
+
+impl NotifySignal {
+    /// Create a signal received by `receiver` on its notification value at `index`.
+    pub fn new(receiver: Task, index: u32) -> NotifySignal {
+        NotifySignal { receiver, index }
+    }
+
+    /// Get the task that receives the signal.
+    pub fn receiver(&self) -> &Task {
+        &self.receiver
+    }
+
+    /// Signal the receiving task. Signals that are not yet taken are not counted.
+    pub fn signal(&self) {
+        notify(&self.receiver, self.index, TaskNotification::Increment);
+    }
+
+    /// Signal the receiving task from an interrupt.
+    pub fn signal_from_isr(&self, context: &mut InterruptContext) {
+        notify_from_isr(&self.receiver, context, self.index, TaskNotification::Increment);
+    }
+
+    /// Wait for the signal. Must be called from the receiving task.
+    ///
+    /// Returns `true` if the signal was received, `false` on timeout.
+    pub fn wait<D: DurationTicks>(&self, max_wait: D) -> bool {
+        take(&self.receiver, self.index, true, max_wait) != 0
+    }
+}
+
+/// A counting semaphore that can only be taken by one task.
+#[derive(Debug, Clone)]
+pub struct NotifyCounter {
+    receiver: Task,
+    index: u32,
+}
+
+impl NotifyCounter {
+    /// Create a counter received by `receiver` on its notification value at `index`.
+    pub fn new(receiver: Task, index: u32) -> NotifyCounter {
+        NotifyCounter { receiver, index }
+    }
+
+    /// Get the task that takes from the counter.
+    pub fn receiver(&self) -> &Task {
+        &self.receiver
+    }
+
+    /// Increment the counter.
+    pub fn give(&self) {
+        notify(&self.receiver, self.index, TaskNotification::Increment);
+    }
+
+    /// Increment the counter from an interrupt.
+    pub fn give_from_isr(&self, context: &mut InterruptContext) {
+        notify_from_isr(&self.receiver, context, self.index, TaskNotification::Increment);
+    }
+
+    /// Wait for the counter to be non-zero and decrement it by one. Must be called
+    /// from the receiving task.
+    ///
+    /// Returns `true` if the counter was decremented, `false` on timeout.
+    pub fn take<D: DurationTicks>(&self, max_wait: D) -> bool {
+        take(&self.receiver, self.index, false, max_wait) != 0
+    }
+
+    /// Wait for the counter to be non-zero and reset it to zero. Must be called
+    /// from the receiving task.
+    ///
+    /// Returns the count before it was reset, `0` on timeout.
+    pub fn take_all<D: DurationTicks>(&self, max_wait: D) -> u32 {
+        take(&self.receiver, self.index, true, max_wait)
+    }
+}
+
+/// A set of event flags that only one task waits for.
+#[derive(Debug, Clone)]
+pub struct EventBits {
+    receiver: Task,
+    index: u32,
+}
+
+impl EventBits {
+    /// Create event flags received by `receiver` on its notification value at `index`.
+    pub fn new(receiver: Task, index: u32) -> EventBits {
+        EventBits { receiver, index }
+    }
+
+    /// Get the task that waits for the flags.
+    pub fn receiver(&self) -> &Task {
+        &self.receiver
+    }
+
+    /// Set flags and unblock the receiving task.
+    pub fn set(&self, bits: u32) {
+        notify(&self.receiver, self.index, TaskNotification::SetBits(bits));
+    }
+
+    /// Set flags and unblock the receiving task, from an interrupt.
+    pub fn set_from_isr(&self, context: &mut InterruptContext, bits: u32) {
+        notify_from_isr(&self.receiver, context, self.index, TaskNotification::SetBits(bits));
+    }
+
+    /// Wait for any flag to be set, then return and clear all flags. Must be called
+    /// from the receiving task.
+    pub fn wait<D: DurationTicks>(&self, max_wait: D) -> Result<u32, FreeRtosError> {
+        debug_assert!(
+            is_current(&self.receiver),
+            "only the receiving task may wait for its notifications"
+        );
+
+        if self.index == 0 {
+            self.receiver.wait_for_notification(0, u32::MAX, max_wait)
+        } else {
+            CurrentTask::wait_for_notification_indexed(self.index, 0, u32::MAX, max_wait)
+        }
+    }
+}
@@ -1,3 +1,2 @@
-pub mod compute_task;
 pub mod processor;
 pub mod pub_sub;
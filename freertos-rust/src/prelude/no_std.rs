@@ -5,7 +5,11 @@ pub use core::marker::PhantomData;
 pub use core::mem;
 pub use core::ops::{Deref, DerefMut};
 
+#[cfg(feature = "alloc")]
 pub use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
 pub use alloc::string::*;
+#[cfg(feature = "alloc")]
 pub use alloc::sync::{Arc, Weak};
+#[cfg(feature = "alloc")]
 pub use alloc::vec::Vec;
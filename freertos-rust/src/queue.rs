@@ -5,7 +5,18 @@ use crate::base::*;
 use crate::isr::*;
 use crate::prelude::v1::*;
 use crate::shim::*;
+#[cfg(feature = "static_alloc")]
+use crate::static_alloc::*;
 use crate::units::*;
+#[cfg(feature = "executor")]
+use crate::waker::*;
+
+#[cfg(feature = "executor")]
+use core::future::poll_fn;
+#[cfg(feature = "executor")]
+use core::pin::pin;
+#[cfg(feature = "executor")]
+use core::task::Poll;
 
 unsafe impl<T: Sized + Send> Send for Queue<T> {}
 unsafe impl<T: Sized + Send> Sync for Queue<T> {}
@@ -31,6 +42,8 @@ impl<T> SendError<T> {
 pub struct Queue<T: Sized + Send> {
     queue: FreeRtosQueueHandle,
     item_type: PhantomData<T>,
+    #[cfg(feature = "executor")]
+    waker: WakerList,
 }
 
 impl<T: Sized + Send> Queue<T> {
@@ -46,6 +59,62 @@ impl<T: Sized + Send> Queue<T> {
         Ok(Queue {
             queue: handle,
             item_type: PhantomData,
+            #[cfg(feature = "executor")]
+            waker: WakerList::new(),
+        })
+    }
+
+    /// Create a queue in caller provided storage, without using the FreeRTOS heap.
+    /// The queue can hold `storage.len()` items, which must be at least one.
+    ///
+    /// Sample usage:
+    ///
+    /// ```rust
+    /// # use freertos_rs::*;
+    /// # use core::mem::MaybeUninit;
+    /// # use core::ptr::addr_of_mut;
+    /// static mut BUFFER: StaticQueueBuffer = StaticQueueBuffer::new();
+    /// static mut STORAGE: [MaybeUninit<u32>; 8] = [MaybeUninit::uninit(); 8];
+    ///
+    /// let q = unsafe {
+    ///     Queue::new_static(&mut *addr_of_mut!(BUFFER), &mut *addr_of_mut!(STORAGE))
+    /// }.unwrap();
+    /// ```
+    #[cfg(feature = "static_alloc")]
+    pub fn new_static(
+        buffer: &'static mut StaticQueueBuffer,
+        storage: &'static mut [MaybeUninit<T>],
+    ) -> Result<Queue<T>, FreeRtosError> {
+        if storage.is_empty() {
+            return Err(FreeRtosError::InvalidQueueSize);
+        }
+
+        let item_size = mem::size_of::<T>();
+        // FreeRTOS expects no storage area for queues of zero-sized items.
+        let storage_ptr = if item_size == 0 {
+            0 as *mut _
+        } else {
+            storage.as_mut_ptr() as FreeRtosMutVoidPtr
+        };
+
+        let handle = unsafe {
+            freertos_rs_queue_create_static(
+                storage.len() as u32,
+                item_size as u32,
+                storage_ptr,
+                buffer.as_mut_ptr()?,
+            )
+        };
+
+        if handle == 0 as *const _ {
+            return Err(FreeRtosError::OutOfMemory);
+        }
+
+        Ok(Queue {
+            queue: handle,
+            item_type: PhantomData,
+            #[cfg(feature = "executor")]
+            waker: WakerList::new(),
         })
     }
 
@@ -59,6 +128,8 @@ impl<T: Sized + Send> Queue<T> {
         Self {
             queue: handle,
             item_type: PhantomData,
+            #[cfg(feature = "executor")]
+            waker: WakerList::new(),
         }
     }
     #[inline]
@@ -78,6 +149,8 @@ impl<T: Sized + Send> Queue<T> {
                     item: ManuallyDrop::into_inner(item),
                 })
             } else {
+                #[cfg(feature = "executor")]
+                self.waker.wake();
                 Ok(())
             }
         }
@@ -99,6 +172,51 @@ impl<T: Sized + Send> Queue<T> {
                     item: ManuallyDrop::into_inner(item),
                 })
             } else {
+                #[cfg(feature = "executor")]
+                self.waker.wake_from_isr(context);
+                Ok(())
+            }
+        }
+    }
+
+    /// Send an item to the front of the queue, ahead of the items already waiting.
+    /// Wait for the queue to have empty space for it.
+    pub fn send_to_front<D: DurationTicks>(&self, item: T, max_wait: D) -> Result<(), SendError<T>> {
+        let item = ManuallyDrop::new(item);
+        let ptr = &item as *const _ as FreeRtosVoidPtr;
+
+        unsafe {
+            if freertos_rs_queue_send_to_front(self.queue, ptr, max_wait.to_ticks()) != 0 {
+                Err(SendError {
+                    err: FreeRtosError::QueueSendTimeout,
+                    item: ManuallyDrop::into_inner(item),
+                })
+            } else {
+                #[cfg(feature = "executor")]
+                self.waker.wake();
+                Ok(())
+            }
+        }
+    }
+
+    /// Send an item to the front of the queue, from an interrupt.
+    pub fn send_to_front_from_isr(
+        &self,
+        context: &mut InterruptContext,
+        item: T,
+    ) -> Result<(), SendError<T>> {
+        let item = ManuallyDrop::new(item);
+        let ptr = &item as *const _ as FreeRtosVoidPtr;
+
+        unsafe {
+            if freertos_rs_queue_send_to_front_isr(self.queue, ptr, context.get_task_field_mut()) != 0 {
+                Err(SendError {
+                    err: FreeRtosError::QueueFull,
+                    item: ManuallyDrop::into_inner(item),
+                })
+            } else {
+                #[cfg(feature = "executor")]
+                self.waker.wake_from_isr(context);
                 Ok(())
             }
         }
@@ -123,10 +241,119 @@ impl<T: Sized + Send> Queue<T> {
         }
     }
 
+    /// Wait for an item to be available on the queue without blocking the task.
+    ///
+    /// The future is woken by sending to the queue through this handle, also from an
+    /// interrupt. Any number of futures may wait on a queue.
+    #[cfg(feature = "executor")]
+    pub async fn recv_async(&self) -> T {
+        let registration = pin!(self.waker.registration());
+        poll_fn(|cx| {
+            if let Ok(item) = self.receive(Duration::zero()) {
+                return Poll::Ready(item);
+            }
+
+            registration.as_ref().register(cx.waker());
+
+            // An item sent before the waker was registered didn't wake anyone.
+            match self.receive(Duration::zero()) {
+                Ok(item) => Poll::Ready(item),
+                Err(_) => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Take an item from the queue, from an interrupt.
+    pub fn receive_from_isr(&self, context: &mut InterruptContext) -> Result<T, FreeRtosError> {
+        unsafe {
+            let mut buff = MaybeUninit::uninit();
+            let r = freertos_rs_queue_receive_isr(
+                self.queue,
+                &mut buff as *mut _ as FreeRtosMutVoidPtr,
+                context.get_task_field_mut(),
+            );
+            if r == 0 {
+                Ok(buff.assume_init())
+            } else {
+                Err(FreeRtosError::QueueReceiveTimeout)
+            }
+        }
+    }
+
+    /// Discard all items in the queue. Items that need to be dropped are received and
+    /// dropped one by one, other items are discarded all at once.
+    pub fn reset(&self) {
+        if mem::needs_drop::<T>() {
+            while self.receive(Duration::zero()).is_ok() {}
+        } else {
+            unsafe { freertos_rs_queue_reset(self.queue) }
+        }
+    }
+
     /// Get the number of messages in the queue.
     pub fn len(&self) -> u32 {
         unsafe { freertos_rs_queue_messages_waiting(self.queue) }
     }
+
+    /// Get the number of messages in the queue, from an interrupt.
+    pub fn len_from_isr(&self, _context: &mut InterruptContext) -> u32 {
+        unsafe { freertos_rs_queue_messages_waiting_isr(self.queue) }
+    }
+
+    /// Get the number of items that can still be sent to the queue.
+    pub fn spaces_available(&self) -> u32 {
+        unsafe { freertos_rs_queue_spaces_available(self.queue) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.spaces_available() == 0
+    }
+
+    pub fn is_empty_from_isr(&self, _context: &mut InterruptContext) -> bool {
+        unsafe { freertos_rs_queue_is_empty_isr(self.queue) == 1 }
+    }
+
+    pub fn is_full_from_isr(&self, _context: &mut InterruptContext) -> bool {
+        unsafe { freertos_rs_queue_is_full_isr(self.queue) == 1 }
+    }
+}
+
+impl<T: Sized + Send + Copy> Queue<T> {
+    /// Wait for an item to be available on the queue and return a copy of it,
+    /// without removing it from the queue.
+    pub fn peek<D: DurationTicks>(&self, max_wait: D) -> Result<T, FreeRtosError> {
+        unsafe {
+            let mut buff = MaybeUninit::uninit();
+            let r = freertos_rs_queue_peek(
+                self.queue,
+                &mut buff as *mut _ as FreeRtosMutVoidPtr,
+                max_wait.to_ticks(),
+            );
+            if r == 0 {
+                Ok(buff.assume_init())
+            } else {
+                Err(FreeRtosError::QueueReceiveTimeout)
+            }
+        }
+    }
+
+    /// Return a copy of the item at the front of the queue, from an interrupt.
+    pub fn peek_from_isr(&self, _context: &mut InterruptContext) -> Result<T, FreeRtosError> {
+        unsafe {
+            let mut buff = MaybeUninit::uninit();
+            let r = freertos_rs_queue_peek_isr(self.queue, &mut buff as *mut _ as FreeRtosMutVoidPtr);
+            if r == 0 {
+                Ok(buff.assume_init())
+            } else {
+                Err(FreeRtosError::QueueReceiveTimeout)
+            }
+        }
+    }
 }
 
 impl<T: Sized + Send> Drop for Queue<T> {
@@ -136,3 +363,75 @@ impl<T: Sized + Send> Drop for Queue<T> {
         }
     }
 }
+
+/// A queue of length one holding the latest value written with [`Mailbox::overwrite`].
+/// Receiving and peeking go through the underlying [`Queue`].
+///
+/// Items are `Copy`, so an item that is overwritten doesn't need to be dropped.
+#[derive(Debug)]
+pub struct Mailbox<T: Sized + Send + Copy> {
+    queue: Queue<T>,
+}
+
+impl<T: Sized + Send + Copy> Mailbox<T> {
+    pub fn new() -> Result<Mailbox<T>, FreeRtosError> {
+        Ok(Mailbox {
+            queue: Queue::new(1)?,
+        })
+    }
+
+    /// Create a mailbox in caller provided storage, without using the FreeRTOS heap.
+    #[cfg(feature = "static_alloc")]
+    pub fn new_static(
+        buffer: &'static mut StaticQueueBuffer,
+        storage: &'static mut [MaybeUninit<T>; 1],
+    ) -> Result<Mailbox<T>, FreeRtosError> {
+        Ok(Mailbox {
+            queue: Queue::new_static(buffer, storage)?,
+        })
+    }
+
+    /// # Safety
+    ///
+    /// `handle` must be a valid FreeRTOS queue handle of length one.
+    ///
+    /// The item size of the queue must match the size of `T`.
+    #[inline]
+    pub unsafe fn from_raw_handle(handle: FreeRtosQueueHandle) -> Self {
+        Mailbox {
+            queue: Queue::from_raw_handle(handle),
+        }
+    }
+
+    /// Write an item to the mailbox, replacing the item in it if it is full.
+    pub fn overwrite(&self, item: T) {
+        let ptr = &item as *const _ as FreeRtosVoidPtr;
+
+        unsafe {
+            freertos_rs_queue_overwrite(self.queue.queue, ptr);
+        }
+
+        #[cfg(feature = "executor")]
+        self.queue.waker.wake();
+    }
+
+    /// Write an item to the mailbox, from an interrupt.
+    pub fn overwrite_from_isr(&self, context: &mut InterruptContext, item: T) {
+        let ptr = &item as *const _ as FreeRtosVoidPtr;
+
+        unsafe {
+            freertos_rs_queue_overwrite_isr(self.queue.queue, ptr, context.get_task_field_mut());
+        }
+
+        #[cfg(feature = "executor")]
+        self.queue.waker.wake_from_isr(context);
+    }
+}
+
+impl<T: Sized + Send + Copy> Deref for Mailbox<T> {
+    type Target = Queue<T>;
+
+    fn deref(&self) -> &Queue<T> {
+        &self.queue
+    }
+}
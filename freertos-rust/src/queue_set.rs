@@ -0,0 +1,176 @@
+use crate::base::*;
+use crate::isr::*;
+use crate::mutex::*;
+use crate::prelude::v1::*;
+use crate::queue::*;
+use crate::semaphore::*;
+use crate::shim::*;
+use crate::units::*;
+
+unsafe impl<K: Copy + Send, const N: usize> Send for QueueSet<'_, K, N> {}
+unsafe impl<K: Copy + Sync, const N: usize> Sync for QueueSet<'_, K, N> {}
+
+/// A set of up to `N` queues and semaphores that a task can block on at the same time.
+///
+/// Requires `configUSE_QUEUE_SETS` and the `queue_set` feature. Every member is added
+/// with a key, which [`select`](Self::select) returns when the member contains data.
+/// Members are borrowed by the set, and removed from it when the set is dropped.
+///
+/// A member can only be added while it is empty, and items must only be taken from a
+/// member after `select` returned its key.
+///
+/// Sample usage:
+///
+/// ```rust
+/// # use freertos_rs::*;
+/// #[derive(Copy, Clone)]
+/// enum Source {
+///     Command,
+///     Tick,
+/// }
+///
+/// let commands: Queue<u32> = Queue::new(4).unwrap();
+/// let tick = Semaphore::new_binary().unwrap();
+///
+/// let mut set = QueueSet::<Source, 2>::new(4 + 1).unwrap();
+/// set.add(&commands, Source::Command).unwrap();
+/// set.add(&tick, Source::Tick).unwrap();
+///
+/// match set.select(Duration::infinite()).unwrap() {
+///     Source::Command => {
+///         let _cmd = commands.receive(Duration::zero()).unwrap();
+///     }
+///     Source::Tick => tick.take(Duration::zero()).unwrap(),
+/// }
+/// ```
+pub struct QueueSet<'a, K: Copy, const N: usize> {
+    queue_set: FreeRtosQueueSetHandle,
+    members: [Option<(FreeRtosQueueSetMemberHandle, K)>; N],
+    _members: PhantomData<&'a ()>,
+}
+
+/// Types that can be added to a [`QueueSet`].
+///
+/// # Safety
+///
+/// `queue_set_member_handle` must return a valid FreeRTOS queue or semaphore handle.
+pub unsafe trait QueueSetMember {
+    fn queue_set_member_handle(&self) -> FreeRtosQueueSetMemberHandle;
+}
+
+unsafe impl<T: Sized + Send> QueueSetMember for Queue<T> {
+    fn queue_set_member_handle(&self) -> FreeRtosQueueSetMemberHandle {
+        self.raw_handle()
+    }
+}
+
+unsafe impl QueueSetMember for Semaphore {
+    fn queue_set_member_handle(&self) -> FreeRtosQueueSetMemberHandle {
+        self.raw_handle()
+    }
+}
+
+unsafe impl QueueSetMember for MutexNormal {
+    fn queue_set_member_handle(&self) -> FreeRtosQueueSetMemberHandle {
+        self.raw_handle()
+    }
+}
+
+impl<'a, K: Copy, const N: usize> QueueSet<'a, K, N> {
+    /// Create a new queue set. `max_events` must be at least the sum of the lengths
+    /// of all queues and the maximum counts of all semaphores that will be added.
+    pub fn new(max_events: usize) -> Result<Self, FreeRtosError> {
+        let handle = unsafe { freertos_rs_queue_set_create(max_events as FreeRtosUBaseType) };
+
+        if handle.is_null() {
+            return Err(FreeRtosError::OutOfMemory);
+        }
+
+        Ok(QueueSet {
+            queue_set: handle,
+            members: [None; N],
+            _members: PhantomData,
+        })
+    }
+
+    #[inline]
+    pub fn raw_handle(&self) -> FreeRtosQueueSetHandle {
+        self.queue_set
+    }
+
+    /// Add a queue or semaphore to the set, to be reported by `key`. Fails if the
+    /// set is full, or if the member is not empty or already part of a set.
+    pub fn add<M: QueueSetMember>(&mut self, member: &'a M, key: K) -> Result<(), FreeRtosError> {
+        let slot = self
+            .members
+            .iter_mut()
+            .find(|m| m.is_none())
+            .ok_or(FreeRtosError::InvalidQueueSetMember)?;
+        let handle = member.queue_set_member_handle();
+
+        if unsafe { freertos_rs_queue_set_add(handle, self.queue_set) } != 0 {
+            return Err(FreeRtosError::InvalidQueueSetMember);
+        }
+
+        *slot = Some((handle, key));
+        Ok(())
+    }
+
+    /// Remove a member from the set and return its key. Fails if the member is not
+    /// empty or is not part of this set.
+    pub fn remove<M: QueueSetMember>(&mut self, member: &M) -> Result<K, FreeRtosError> {
+        let handle = member.queue_set_member_handle();
+        let slot = self
+            .members
+            .iter_mut()
+            .find(|m| matches!(m, Some((h, _)) if *h == handle))
+            .ok_or(FreeRtosError::InvalidQueueSetMember)?;
+
+        if unsafe { freertos_rs_queue_set_remove(handle, self.queue_set) } != 0 {
+            return Err(FreeRtosError::InvalidQueueSetMember);
+        }
+
+        Ok(slot.take().map(|(_, key)| key).unwrap())
+    }
+
+    /// Wait for one of the members to contain data and return its key.
+    pub fn select<D: DurationTicks>(&self, max_wait: D) -> Result<K, FreeRtosError> {
+        let member = unsafe { freertos_rs_queue_set_select(self.queue_set, max_wait.to_ticks()) };
+        self.key(member).ok_or(FreeRtosError::Timeout)
+    }
+
+    /// Get the key of a member that contains data, from an interrupt.
+    pub fn select_from_isr(&self, _context: &mut InterruptContext) -> Option<K> {
+        let member = unsafe { freertos_rs_queue_set_select_isr(self.queue_set) };
+        self.key(member)
+    }
+
+    fn key(&self, member: FreeRtosQueueSetMemberHandle) -> Option<K> {
+        if member.is_null() {
+            return None;
+        }
+
+        self.members
+            .iter()
+            .flatten()
+            .find(|(h, _)| *h == member)
+            .map(|(_, key)| *key)
+    }
+}
+
+impl<K: Copy, const N: usize> Drop for QueueSet<'_, K, N> {
+    fn drop(&mut self) {
+        let mut removed = true;
+        for (handle, _) in self.members.iter().flatten() {
+            removed &= unsafe { freertos_rs_queue_set_remove(*handle, self.queue_set) } == 0;
+        }
+
+        // Members that still contain data can't be removed and keep a pointer to
+        // their set, which is leaked rather than deleted then.
+        if removed {
+            unsafe {
+                freertos_rs_queue_delete(self.queue_set);
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+use crate::base::*;
+use crate::join_handle::*;
+use crate::prelude::v1::*;
+use crate::semaphore::*;
+use crate::task::*;
+use crate::units::*;
+
+#[cfg(feature = "std")]
+use core::sync::atomic::AtomicBool;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Bookkeeping shared between a scope and the tasks spawned in it.
+struct ScopeData {
+    running: AtomicUsize,
+    finished: Semaphore,
+    #[cfg(feature = "std")]
+    a_task_panicked: AtomicBool,
+}
+
+impl ScopeData {
+    fn task_started(&self) {
+        self.running.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Called by a task once it is done with everything borrowed from the scope.
+    /// `returned` is `false` if the task panicked.
+    fn task_finished(&self, returned: bool) {
+        #[cfg(feature = "std")]
+        if !returned {
+            self.a_task_panicked.store(true, Ordering::Relaxed);
+        }
+        #[cfg(not(feature = "std"))]
+        let _ = returned;
+
+        self.running.fetch_sub(1, Ordering::Release);
+        self.finished.give();
+    }
+}
+
+/// A scope to spawn tasks in, created with [`Task::scope`].
+///
+/// Tasks spawned in a scope may borrow data from outside of it, because all of them
+/// have finished before [`Task::scope`] returns.
+pub struct Scope<'scope, 'env: 'scope> {
+    data: Arc<ScopeData>,
+    scope: PhantomData<&'scope mut &'scope ()>,
+    env: PhantomData<&'env mut &'env ()>,
+}
+
+/// An owned permission to wait for a scoped task to finish and take its return value.
+pub struct ScopedJoinHandle<'scope, R> {
+    task: Task,
+    packet: Arc<Packet<R>>,
+    scope: PhantomData<&'scope ()>,
+}
+
+/// Error returned by [`ScopedJoinHandle::join`].
+#[derive(Debug)]
+pub enum ScopedJoinError<'scope, R> {
+    /// The task did not finish in time. The handle is given back to wait again.
+    Timeout(ScopedJoinHandle<'scope, R>),
+    /// The task panicked. Only reported with the `std` feature.
+    Panicked,
+}
+
+impl Task {
+    /// Create a scope for spawning tasks that borrow from the current stack frame.
+    ///
+    /// All tasks spawned in the scope that were not joined are waited for before
+    /// this function returns. Fails if the scope's semaphore can't be created.
+    ///
+    /// Sample usage:
+    ///
+    /// ```rust
+    /// # use freertos_rs::*;
+    /// let samples = [0i32; 256];
+    ///
+    /// let (low, high) = Task::scope(|s| {
+    ///     let low = s.spawn(|_| samples[..128].iter().sum::<i32>()).unwrap();
+    ///     let high = s.spawn(|_| samples[128..].iter().sum::<i32>()).unwrap();
+    ///
+    ///     (
+    ///         low.join(Duration::infinite()).unwrap(),
+    ///         high.join(Duration::infinite()).unwrap(),
+    ///     )
+    /// }).unwrap();
+    /// ```
+    pub fn scope<'env, F, T>(f: F) -> Result<T, FreeRtosError>
+    where
+        F: for<'scope> FnOnce(&'scope Scope<'scope, 'env>) -> T,
+    {
+        let scope = Scope {
+            data: Arc::new(ScopeData {
+                running: AtomicUsize::new(0),
+                finished: Semaphore::new_binary()?,
+                #[cfg(feature = "std")]
+                a_task_panicked: AtomicBool::new(false),
+            }),
+            scope: PhantomData,
+            env: PhantomData,
+        };
+
+        #[cfg(feature = "std")]
+        let result = std::panic::catch_unwind(core::panic::AssertUnwindSafe(|| f(&scope)));
+        #[cfg(not(feature = "std"))]
+        let result = f(&scope);
+
+        // Every finished task gives the semaphore once, so re-check the count after each wake up.
+        while scope.data.running.load(Ordering::Acquire) != 0 {
+            let _ = scope.data.finished.take(Duration::infinite());
+        }
+
+        #[cfg(feature = "std")]
+        let result = match result {
+            Err(e) => std::panic::resume_unwind(e),
+            Ok(_) if scope.data.a_task_panicked.load(Ordering::Relaxed) => {
+                panic!("a scoped task panicked")
+            }
+            Ok(result) => result,
+        };
+
+        Ok(result)
+    }
+}
+
+impl<'scope, 'env> Scope<'scope, 'env> {
+    /// Spawn a new task with the default settings of [`Task::new`].
+    pub fn spawn<F, R>(&'scope self, func: F) -> Result<ScopedJoinHandle<'scope, R>, FreeRtosError>
+    where
+        F: FnOnce(Task) -> R,
+        F: Send + 'scope,
+        R: Send + 'scope,
+    {
+        Task::new().spawn_scoped(self, func)
+    }
+}
+
+impl TaskBuilder {
+    /// Start a new task in `scope`.
+    pub fn spawn_scoped<'scope, 'env, F, R>(
+        &self,
+        scope: &'scope Scope<'scope, 'env>,
+        func: F,
+    ) -> Result<ScopedJoinHandle<'scope, R>, FreeRtosError>
+    where
+        F: FnOnce(Task) -> R,
+        F: Send + 'scope,
+        R: Send + 'scope,
+    {
+        let packet = Arc::new(Packet::new()?);
+
+        let their_packet = packet.clone();
+        let their_scope = scope.data.clone();
+        scope.data.task_started();
+        // SAFETY: the scope does not end before the task is done with the closure and
+        // its packet. The result is then either dropped, or owned by the join handle,
+        // which can't outlive the scope.
+        let task = unsafe {
+            self.start_unchecked(Box::new(move |this_task| {
+                let returned = their_packet.run(this_task, func);
+                drop(their_packet);
+                their_scope.task_finished(returned);
+            }))
+        };
+
+        let task = match task {
+            Ok(task) => task,
+            Err(e) => {
+                scope.data.task_finished(true);
+                return Err(e);
+            }
+        };
+
+        Ok(ScopedJoinHandle {
+            task,
+            packet,
+            scope: PhantomData,
+        })
+    }
+}
+
+impl<'scope, R> ScopedJoinHandle<'scope, R> {
+    /// Get the handle of the spawned task.
+    pub fn task(&self) -> &Task {
+        &self.task
+    }
+
+    /// Check whether the task has finished, without blocking.
+    pub fn is_finished(&self) -> bool {
+        self.packet.is_finished()
+    }
+
+    /// Wait for the task to finish and take its return value. A panic of the task is
+    /// only detected with the `std` feature, see [`JoinHandle::join`].
+    pub fn join<D: DurationTicks>(self, max_wait: D) -> Result<R, ScopedJoinError<'scope, R>> {
+        match self.packet.join(max_wait) {
+            Some(Some(r)) => Ok(r),
+            Some(None) => Err(ScopedJoinError::Panicked),
+            None => Err(ScopedJoinError::Timeout(self)),
+        }
+    }
+}
+
+impl<'scope, R> fmt::Debug for ScopedJoinHandle<'scope, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScopedJoinHandle")
+            .field("task", &self.task)
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
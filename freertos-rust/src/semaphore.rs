@@ -1,11 +1,24 @@
 use crate::base::*;
 use crate::isr::*;
 use crate::shim::*;
+#[cfg(feature = "static_alloc")]
+use crate::static_alloc::*;
 use crate::units::*;
+#[cfg(feature = "executor")]
+use crate::waker::*;
+
+#[cfg(feature = "executor")]
+use core::future::poll_fn;
+#[cfg(feature = "executor")]
+use core::pin::pin;
+#[cfg(feature = "executor")]
+use core::task::Poll;
 
 /// A counting or binary semaphore
 pub struct Semaphore {
     semaphore: FreeRtosSemaphoreHandle,
+    #[cfg(feature = "executor")]
+    waker: WakerList,
 }
 
 unsafe impl Send for Semaphore {}
@@ -19,7 +32,7 @@ impl Semaphore {
             if s == 0 as *const _ {
                 return Err(FreeRtosError::OutOfMemory);
             }
-            Ok(Semaphore { semaphore: s })
+            Ok(Semaphore::from_raw_handle(s))
         }
     }
 
@@ -30,7 +43,35 @@ impl Semaphore {
             if s == 0 as *const _ {
                 return Err(FreeRtosError::OutOfMemory);
             }
-            Ok(Semaphore { semaphore: s })
+            Ok(Semaphore::from_raw_handle(s))
+        }
+    }
+
+    /// Create a new binary semaphore in caller provided storage
+    #[cfg(feature = "static_alloc")]
+    pub fn new_binary_static(buffer: &'static mut StaticSemaphoreBuffer) -> Result<Semaphore, FreeRtosError> {
+        unsafe {
+            let s = freertos_rs_create_binary_semaphore_static(buffer.as_mut_ptr()?);
+            if s == 0 as *const _ {
+                return Err(FreeRtosError::OutOfMemory);
+            }
+            Ok(Semaphore::from_raw_handle(s))
+        }
+    }
+
+    /// Create a new counting semaphore in caller provided storage
+    #[cfg(feature = "static_alloc")]
+    pub fn new_counting_static(
+        max: u32,
+        initial: u32,
+        buffer: &'static mut StaticSemaphoreBuffer,
+    ) -> Result<Semaphore, FreeRtosError> {
+        unsafe {
+            let s = freertos_rs_create_counting_semaphore_static(max, initial, buffer.as_mut_ptr()?);
+            if s == 0 as *const _ {
+                return Err(FreeRtosError::OutOfMemory);
+            }
+            Ok(Semaphore::from_raw_handle(s))
         }
     }
 
@@ -42,7 +83,11 @@ impl Semaphore {
     /// To create mutex from raw handle use [`crate::mutex::MutexInnerImpl::from_raw_handle`].
     #[inline]
     pub unsafe fn from_raw_handle(handle: FreeRtosSemaphoreHandle) -> Self {
-        Self { semaphore: handle }
+        Self {
+            semaphore: handle,
+            #[cfg(feature = "executor")]
+            waker: WakerList::new(),
+        }
     }
     #[inline]
     pub fn raw_handle(&self) -> FreeRtosSemaphoreHandle {
@@ -56,7 +101,14 @@ impl Semaphore {
 
     /// Returns `true` on success, `false` when semaphore count already reached its limit
     pub fn give(&self) -> bool {
-        unsafe { freertos_rs_give_semaphore(self.semaphore) == 0 }
+        let given = unsafe { freertos_rs_give_semaphore(self.semaphore) == 0 };
+
+        #[cfg(feature = "executor")]
+        if given {
+            self.waker.wake();
+        }
+
+        given
     }
 
     pub fn take<D: DurationTicks>(&self, max_wait: D) -> Result<(), FreeRtosError> {
@@ -71,9 +123,40 @@ impl Semaphore {
         }
     }
 
+    /// Wait for the semaphore to be available and take it, without blocking the task.
+    ///
+    /// The future is woken by giving the semaphore through this handle, also from an
+    /// interrupt. Any number of futures may wait on a semaphore.
+    #[cfg(feature = "executor")]
+    pub async fn acquire_async(&self) {
+        let registration = pin!(self.waker.registration());
+        poll_fn(|cx| {
+            if self.take(Duration::zero()).is_ok() {
+                return Poll::Ready(());
+            }
+
+            registration.as_ref().register(cx.waker());
+
+            // A give before the waker was registered didn't wake anyone.
+            if self.take(Duration::zero()).is_ok() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
     /// Returns `true` on success, `false` when semaphore count already reached its limit
     pub fn give_from_isr(&self, context: &mut InterruptContext) -> bool {
-        unsafe { freertos_rs_give_semaphore_isr(self.semaphore, context.get_task_field_mut()) == 0 }
+        let given = unsafe { freertos_rs_give_semaphore_isr(self.semaphore, context.get_task_field_mut()) == 0 };
+
+        #[cfg(feature = "executor")]
+        if given {
+            self.waker.wake_from_isr(context);
+        }
+
+        given
     }
 
     /// Returns `true` on success, `false` if the semaphore was not successfully taken because it was not available
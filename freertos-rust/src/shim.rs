@@ -10,6 +10,7 @@ extern "C" {
     pub fn freertos_rs_vPortFree(pv: FreeRtosVoidPtr);
 
     pub fn freertos_rs_sizeof(_type: u8) -> u8;
+    pub fn freertos_rs_sizeof_static(_type: u8) -> usize;
 
     pub fn freertos_rs_vTaskDelayUntil(
         pxPreviousWakeTime: *mut FreeRtosTickType,
@@ -26,6 +27,10 @@ extern "C" {
 
     pub fn freertos_rs_create_recursive_mutex() -> FreeRtosSemaphoreHandle;
     pub fn freertos_rs_create_mutex() -> FreeRtosSemaphoreHandle;
+    #[cfg(feature = "mutex_holder")]
+    pub fn freertos_rs_get_mutex_holder(mutex: FreeRtosSemaphoreHandle) -> FreeRtosTaskHandle;
+    #[cfg(feature = "mutex_holder")]
+    pub fn freertos_rs_get_mutex_holder_isr(mutex: FreeRtosSemaphoreHandle) -> FreeRtosTaskHandle;
 
     pub fn freertos_rs_take_recursive_semaphore(
         semaphore: FreeRtosSemaphoreHandle,
@@ -75,6 +80,60 @@ extern "C" {
     pub fn freertos_rs_queue_messages_waiting(
         queue: FreeRtosQueueHandle,
     ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_messages_waiting_isr(
+        queue: FreeRtosQueueHandle,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_spaces_available(queue: FreeRtosQueueHandle) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_send_to_front(
+        queue: FreeRtosQueueHandle,
+        item: FreeRtosVoidPtr,
+        max_wait: FreeRtosTickType,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_send_to_front_isr(
+        queue: FreeRtosQueueHandle,
+        item: FreeRtosVoidPtr,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_overwrite(queue: FreeRtosQueueHandle, item: FreeRtosVoidPtr);
+    pub fn freertos_rs_queue_overwrite_isr(
+        queue: FreeRtosQueueHandle,
+        item: FreeRtosVoidPtr,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    );
+    pub fn freertos_rs_queue_receive_isr(
+        queue: FreeRtosQueueHandle,
+        item: FreeRtosMutVoidPtr,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_peek(
+        queue: FreeRtosQueueHandle,
+        item: FreeRtosMutVoidPtr,
+        max_wait: FreeRtosTickType,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_peek_isr(
+        queue: FreeRtosQueueHandle,
+        item: FreeRtosMutVoidPtr,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_reset(queue: FreeRtosQueueHandle);
+    pub fn freertos_rs_queue_is_full_isr(queue: FreeRtosQueueHandle) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_is_empty_isr(queue: FreeRtosQueueHandle) -> FreeRtosUBaseType;
+
+    pub fn freertos_rs_queue_set_create(event_queue_length: FreeRtosUBaseType) -> FreeRtosQueueSetHandle;
+    pub fn freertos_rs_queue_set_add(
+        member: FreeRtosQueueSetMemberHandle,
+        queue_set: FreeRtosQueueSetHandle,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_set_remove(
+        member: FreeRtosQueueSetMemberHandle,
+        queue_set: FreeRtosQueueSetHandle,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_set_select(
+        queue_set: FreeRtosQueueSetHandle,
+        max_wait: FreeRtosTickType,
+    ) -> FreeRtosQueueSetMemberHandle;
+    pub fn freertos_rs_queue_set_select_isr(
+        queue_set: FreeRtosQueueSetHandle,
+    ) -> FreeRtosQueueSetMemberHandle;
 
     pub fn freertos_rs_queue_send_isr(
         queue: FreeRtosQueueHandle,
@@ -102,6 +161,70 @@ extern "C" {
         action: u8,
         xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
     ) -> FreeRtosBaseType;
+    pub fn freertos_rs_task_notify_and_query(
+        task: FreeRtosTaskHandle,
+        value: u32,
+        action: u8,
+        previous_value: *mut u32,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_task_notify_and_query_isr(
+        task: FreeRtosTaskHandle,
+        value: u32,
+        action: u8,
+        previous_value: *mut u32,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_task_notify_state_clear(task: FreeRtosTaskHandle) -> FreeRtosUBaseType;
+
+    pub fn freertos_rs_task_notify_take_indexed(
+        index: FreeRtosUBaseType,
+        clear_count: u8,
+        wait: FreeRtosTickType,
+    ) -> u32;
+    pub fn freertos_rs_task_notify_wait_indexed(
+        index: FreeRtosUBaseType,
+        ulBitsToClearOnEntry: u32,
+        ulBitsToClearOnExit: u32,
+        pulNotificationValue: *mut u32,
+        xTicksToWait: FreeRtosTickType,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_task_notify_indexed(
+        task: FreeRtosTaskHandle,
+        index: FreeRtosUBaseType,
+        value: u32,
+        action: u8,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_task_notify_indexed_isr(
+        task: FreeRtosTaskHandle,
+        index: FreeRtosUBaseType,
+        value: u32,
+        action: u8,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_task_notify_and_query_indexed(
+        task: FreeRtosTaskHandle,
+        index: FreeRtosUBaseType,
+        value: u32,
+        action: u8,
+        previous_value: *mut u32,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_task_notify_and_query_indexed_isr(
+        task: FreeRtosTaskHandle,
+        index: FreeRtosUBaseType,
+        value: u32,
+        action: u8,
+        previous_value: *mut u32,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_task_notify_state_clear_indexed(
+        task: FreeRtosTaskHandle,
+        index: FreeRtosUBaseType,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_task_notify_value_clear_indexed(
+        task: FreeRtosTaskHandle,
+        index: FreeRtosUBaseType,
+        bits_to_clear: u32,
+    ) -> u32;
 
     pub fn freertos_rs_spawn_task(
         f: extern "C" fn(FreeRtosMutVoidPtr) -> FreeRtosMutVoidPtr,
@@ -114,6 +237,16 @@ extern "C" {
     ) -> FreeRtosUBaseType;
     pub fn freertos_rs_delete_task(task: FreeRtosTaskHandle);
     pub fn freertos_rs_suspend_task(task: FreeRtosTaskHandle);
+    pub fn freertos_rs_resume_task(task: FreeRtosTaskHandle);
+    pub fn freertos_rs_resume_task_isr(
+        task: FreeRtosTaskHandle,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    );
+    pub fn freertos_rs_task_priority_get(task: FreeRtosTaskHandle) -> FreeRtosUBaseType;
+    pub fn freertos_rs_task_priority_set(task: FreeRtosTaskHandle, priority: FreeRtosUBaseType);
+    pub fn freertos_rs_task_get_state(task: FreeRtosTaskHandle) -> u8;
+    #[cfg(feature = "abort_delay")]
+    pub fn freertos_rs_task_abort_delay(task: FreeRtosTaskHandle) -> FreeRtosUBaseType;
     pub fn freertos_rs_vTaskSuspendAll();
     pub fn freertos_rs_xTaskResumeAll() -> FreeRtosBaseType;
 
@@ -125,6 +258,25 @@ extern "C" {
 
     pub fn freertos_rs_task_get_name(task: FreeRtosTaskHandle) -> FreeRtosCharPtr;
     pub fn freertos_rs_get_stack_high_water_mark(task: FreeRtosTaskHandle) -> FreeRtosBaseType;
+    #[cfg(feature = "task_tag")]
+    pub fn freertos_rs_task_set_application_tag(task: FreeRtosTaskHandle, tag: FreeRtosMutVoidPtr);
+    #[cfg(feature = "task_tag")]
+    pub fn freertos_rs_task_get_application_tag(task: FreeRtosTaskHandle) -> FreeRtosMutVoidPtr;
+    #[cfg(feature = "task_tag")]
+    pub fn freertos_rs_task_call_application_hook(
+        task: FreeRtosTaskHandle,
+        parameter: FreeRtosMutVoidPtr,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_task_set_tls_pointer(
+        task: FreeRtosTaskHandle,
+        index: FreeRtosBaseType,
+        value: FreeRtosMutVoidPtr,
+    );
+    pub fn freertos_rs_task_get_tls_pointer(
+        task: FreeRtosTaskHandle,
+        index: FreeRtosBaseType,
+    ) -> FreeRtosMutVoidPtr;
+    pub fn freertos_rs_task_local_index() -> FreeRtosBaseType;
 
     pub fn freertos_rs_get_current_task() -> FreeRtosTaskHandle;
     pub fn freertos_rs_get_system_state(
@@ -137,7 +289,6 @@ extern "C" {
 
     pub fn freertos_rs_timer_create(
         name: FreeRtosCharPtr,
-        name_len: u8,
         period: FreeRtosTickType,
         auto_reload: u8,
         timer_id: FreeRtosVoidPtr,
@@ -164,10 +315,47 @@ extern "C" {
         block_time: FreeRtosTickType,
         new_period: FreeRtosTickType,
     ) -> FreeRtosBaseType;
+    pub fn freertos_rs_timer_stop_from_isr(
+        timer: FreeRtosTimerHandle,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_timer_change_period_from_isr(
+        timer: FreeRtosTimerHandle,
+        new_period: FreeRtosTickType,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_timer_reset(
+        timer: FreeRtosTimerHandle,
+        block_time: FreeRtosTickType,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_timer_reset_from_isr(
+        timer: FreeRtosTimerHandle,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_timer_is_active(timer: FreeRtosTimerHandle) -> FreeRtosBaseType;
+    pub fn freertos_rs_timer_get_period(timer: FreeRtosTimerHandle) -> FreeRtosTickType;
+    pub fn freertos_rs_timer_get_expiry_time(timer: FreeRtosTimerHandle) -> FreeRtosTickType;
+    pub fn freertos_rs_timer_set_reload_mode(timer: FreeRtosTimerHandle, auto_reload: u8);
+    pub fn freertos_rs_timer_get_name(timer: FreeRtosTimerHandle) -> FreeRtosCharPtr;
     pub fn freertos_rs_timer_get_id(timer: FreeRtosTimerHandle) -> FreeRtosVoidPtr;
+    pub fn freertos_rs_pend_function_call(
+        function: FreeRtosPendedFunction,
+        parameter1: FreeRtosMutVoidPtr,
+        parameter2: u32,
+        block_time: FreeRtosTickType,
+    ) -> FreeRtosBaseType;
+    pub fn freertos_rs_pend_function_call_isr(
+        function: FreeRtosPendedFunction,
+        parameter1: FreeRtosMutVoidPtr,
+        parameter2: u32,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> FreeRtosBaseType;
 
     pub fn freertos_rs_enter_critical();
     pub fn freertos_rs_exit_critical();
+    pub fn freertos_rs_enter_critical_from_isr() -> FreeRtosUBaseType;
+    pub fn freertos_rs_exit_critical_from_isr(saved_interrupt_status: FreeRtosUBaseType);
+    pub fn freertos_rs_is_in_isr() -> FreeRtosUBaseType;
 
     pub fn freertos_rs_event_group_create() -> FreeRtosEventGroupHandle;
     pub fn freertos_rs_event_group_delete(event_group: FreeRtosEventGroupHandle);
@@ -176,4 +364,139 @@ extern "C" {
     pub fn freertos_rs_event_group_clear_bits(event_group: FreeRtosEventGroupHandle, bits_to_clear: FreeRtosEventBitsType) -> FreeRtosEventBitsType;
     pub fn freertos_rs_event_group_wait_bits(event_group: FreeRtosEventGroupHandle, bits_to_wait_for: FreeRtosEventBitsType, clear_on_exit: FreeRtosBaseType, wait_for_all_bits: FreeRtosBaseType, ticks_to_wait: FreeRtosTickType) -> FreeRtosEventBitsType;
     pub fn freertos_rs_event_group_sync(event_group: FreeRtosEventGroupHandle, bits_to_set: FreeRtosEventBitsType, bits_to_wait_for: FreeRtosEventBitsType, ticks_to_wait: FreeRtosTickType) -> FreeRtosEventBitsType;
+    pub fn freertos_rs_event_group_set_bits_isr(event_group: FreeRtosEventGroupHandle, bits_to_set: FreeRtosEventBitsType, xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr) -> FreeRtosBaseType;
+
+    pub fn freertos_rs_stream_buffer_create(
+        buffer_size: usize,
+        trigger_level: usize,
+    ) -> FreeRtosStreamBufferHandle;
+    pub fn freertos_rs_stream_buffer_delete(stream_buffer: FreeRtosStreamBufferHandle);
+    pub fn freertos_rs_stream_buffer_send(
+        stream_buffer: FreeRtosStreamBufferHandle,
+        data: FreeRtosVoidPtr,
+        data_len: usize,
+        max_wait: FreeRtosTickType,
+    ) -> usize;
+    pub fn freertos_rs_stream_buffer_send_isr(
+        stream_buffer: FreeRtosStreamBufferHandle,
+        data: FreeRtosVoidPtr,
+        data_len: usize,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> usize;
+    pub fn freertos_rs_stream_buffer_receive(
+        stream_buffer: FreeRtosStreamBufferHandle,
+        buffer: FreeRtosMutVoidPtr,
+        buffer_len: usize,
+        max_wait: FreeRtosTickType,
+    ) -> usize;
+    pub fn freertos_rs_stream_buffer_receive_isr(
+        stream_buffer: FreeRtosStreamBufferHandle,
+        buffer: FreeRtosMutVoidPtr,
+        buffer_len: usize,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> usize;
+    pub fn freertos_rs_stream_buffer_bytes_available(
+        stream_buffer: FreeRtosStreamBufferHandle,
+    ) -> usize;
+    pub fn freertos_rs_stream_buffer_spaces_available(
+        stream_buffer: FreeRtosStreamBufferHandle,
+    ) -> usize;
+    pub fn freertos_rs_stream_buffer_is_empty(
+        stream_buffer: FreeRtosStreamBufferHandle,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_stream_buffer_is_full(
+        stream_buffer: FreeRtosStreamBufferHandle,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_stream_buffer_reset(
+        stream_buffer: FreeRtosStreamBufferHandle,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_stream_buffer_set_trigger_level(
+        stream_buffer: FreeRtosStreamBufferHandle,
+        trigger_level: usize,
+    ) -> FreeRtosUBaseType;
+
+    pub fn freertos_rs_message_buffer_create(buffer_size: usize) -> FreeRtosMessageBufferHandle;
+    pub fn freertos_rs_message_buffer_delete(message_buffer: FreeRtosMessageBufferHandle);
+    pub fn freertos_rs_message_buffer_send(
+        message_buffer: FreeRtosMessageBufferHandle,
+        data: FreeRtosVoidPtr,
+        data_len: usize,
+        max_wait: FreeRtosTickType,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_message_buffer_send_isr(
+        message_buffer: FreeRtosMessageBufferHandle,
+        data: FreeRtosVoidPtr,
+        data_len: usize,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_message_buffer_receive(
+        message_buffer: FreeRtosMessageBufferHandle,
+        buffer: FreeRtosMutVoidPtr,
+        buffer_len: usize,
+        max_wait: FreeRtosTickType,
+    ) -> usize;
+    pub fn freertos_rs_message_buffer_receive_isr(
+        message_buffer: FreeRtosMessageBufferHandle,
+        buffer: FreeRtosMutVoidPtr,
+        buffer_len: usize,
+        xHigherPriorityTaskWoken: FreeRtosBaseTypeMutPtr,
+    ) -> usize;
+    pub fn freertos_rs_message_buffer_next_length_bytes(
+        message_buffer: FreeRtosMessageBufferHandle,
+    ) -> usize;
+    pub fn freertos_rs_message_buffer_spaces_available(
+        message_buffer: FreeRtosMessageBufferHandle,
+    ) -> usize;
+    pub fn freertos_rs_message_buffer_is_empty(
+        message_buffer: FreeRtosMessageBufferHandle,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_message_buffer_is_full(
+        message_buffer: FreeRtosMessageBufferHandle,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_message_buffer_reset(
+        message_buffer: FreeRtosMessageBufferHandle,
+    ) -> FreeRtosUBaseType;
+
+    pub fn freertos_rs_spawn_task_static(
+        f: extern "C" fn(FreeRtosMutVoidPtr) -> FreeRtosMutVoidPtr,
+        value: FreeRtosMutVoidPtr,
+        name: FreeRtosCharPtr,
+        name_len: u8,
+        stack_size: u32,
+        priority: FreeRtosUBaseType,
+        stack_buffer: FreeRtosMutVoidPtr,
+        task_buffer: FreeRtosMutVoidPtr,
+        task_handle: *mut FreeRtosTaskHandle,
+    ) -> FreeRtosUBaseType;
+    pub fn freertos_rs_queue_create_static(
+        length: FreeRtosUBaseType,
+        item_size: FreeRtosUBaseType,
+        storage: FreeRtosMutVoidPtr,
+        queue_buffer: FreeRtosMutVoidPtr,
+    ) -> FreeRtosQueueHandle;
+    pub fn freertos_rs_create_binary_semaphore_static(
+        semaphore_buffer: FreeRtosMutVoidPtr,
+    ) -> FreeRtosSemaphoreHandle;
+    pub fn freertos_rs_create_counting_semaphore_static(
+        max: FreeRtosUBaseType,
+        initial: FreeRtosUBaseType,
+        semaphore_buffer: FreeRtosMutVoidPtr,
+    ) -> FreeRtosSemaphoreHandle;
+    pub fn freertos_rs_create_mutex_static(
+        semaphore_buffer: FreeRtosMutVoidPtr,
+    ) -> FreeRtosSemaphoreHandle;
+    pub fn freertos_rs_create_recursive_mutex_static(
+        semaphore_buffer: FreeRtosMutVoidPtr,
+    ) -> FreeRtosSemaphoreHandle;
+    pub fn freertos_rs_event_group_create_static(
+        event_group_buffer: FreeRtosMutVoidPtr,
+    ) -> FreeRtosEventGroupHandle;
+    pub fn freertos_rs_timer_create_static(
+        name: FreeRtosCharPtr,
+        period: FreeRtosTickType,
+        auto_reload: u8,
+        timer_id: FreeRtosVoidPtr,
+        callback: extern "C" fn(FreeRtosTimerHandle) -> (),
+        timer_buffer: FreeRtosMutVoidPtr,
+    ) -> FreeRtosTimerHandle;
 }
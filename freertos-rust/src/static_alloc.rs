@@ -0,0 +1,119 @@
+use crate::base::*;
+use crate::shim::*;
+#[cfg(feature = "time")]
+use crate::utils::*;
+use core::mem::{self, MaybeUninit};
+
+// Caller provided storage for FreeRTOS objects that are created without using the heap.
+// The buffers are sized generously; the size of the matching `Static*_t` type of the
+// linked kernel is checked at runtime when an object is created.
+macro_rules! static_buffer {
+    ($(#[$attr:meta])* $name:ident, $words:expr, $kind:expr) => {
+        $(#[$attr])*
+        #[repr(C, align(8))]
+        pub struct $name(MaybeUninit<[usize; $words]>);
+
+        impl $name {
+            pub const fn new() -> Self {
+                $name(MaybeUninit::uninit())
+            }
+
+            pub(crate) fn as_mut_ptr(&mut self) -> Result<FreeRtosMutVoidPtr, FreeRtosError> {
+                if mem::size_of::<Self>() < unsafe { freertos_rs_sizeof_static($kind) } {
+                    return Err(FreeRtosError::StaticBufferTooSmall);
+                }
+
+                Ok(self.0.as_mut_ptr() as FreeRtosMutVoidPtr)
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+static_buffer!(
+    /// Storage for a task control block (`StaticTask_t`).
+    StaticTaskBuffer,
+    96,
+    0
+);
+
+static_buffer!(
+    /// Storage for a queue, semaphore or mutex (`StaticQueue_t`).
+    StaticQueueBuffer,
+    24,
+    1
+);
+
+#[cfg(feature = "time")]
+static_buffer!(
+    /// Storage for the timer control block (`StaticTimer_t`) of a [`StaticTimerBuffer`].
+    StaticTimerControlBuffer,
+    16,
+    2
+);
+
+/// Storage for a software timer and its name, which FreeRTOS doesn't copy.
+#[cfg(feature = "time")]
+pub struct StaticTimerBuffer {
+    timer: StaticTimerControlBuffer,
+    name: NameBuffer,
+}
+
+#[cfg(feature = "time")]
+impl StaticTimerBuffer {
+    pub const fn new() -> Self {
+        StaticTimerBuffer {
+            timer: StaticTimerControlBuffer::new(),
+            name: NameBuffer::empty(),
+        }
+    }
+
+    /// Store `name` and return pointers to the name and the timer storage.
+    pub(crate) fn prepare(&mut self, name: &NameBuffer) -> Result<(FreeRtosCharPtr, FreeRtosMutVoidPtr), FreeRtosError> {
+        let timer = self.timer.as_mut_ptr()?;
+        self.name = *name;
+        Ok((self.name.as_c_str_ptr(), timer))
+    }
+}
+
+#[cfg(feature = "time")]
+impl Default for StaticTimerBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+static_buffer!(
+    /// Storage for an event group (`StaticEventGroup_t`).
+    StaticEventGroupBuffer,
+    12,
+    3
+);
+
+/// Storage for a semaphore or mutex (`StaticSemaphore_t`).
+pub type StaticSemaphoreBuffer = StaticQueueBuffer;
+
+/// The stack of a statically allocated task, `N` words long.
+#[repr(C)]
+pub struct StaticTaskStack<const N: usize>(MaybeUninit<[FreeRtosStackType; N]>);
+
+impl<const N: usize> StaticTaskStack<N> {
+    pub const fn new() -> Self {
+        StaticTaskStack(MaybeUninit::uninit())
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> FreeRtosMutVoidPtr {
+        self.0.as_mut_ptr() as FreeRtosMutVoidPtr
+    }
+}
+
+impl<const N: usize> Default for StaticTaskStack<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
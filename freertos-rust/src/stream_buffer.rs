@@ -0,0 +1,146 @@
+use crate::base::*;
+use crate::isr::*;
+use crate::shim::*;
+use crate::units::*;
+
+unsafe impl Send for StreamBuffer {}
+unsafe impl Sync for StreamBuffer {}
+
+/// A buffer that passes a continuous stream of bytes from a task or an interrupt
+/// to another task.
+///
+/// FreeRTOS stream buffers assume there is only one writer and only one reader.
+/// If several tasks write to (or read from) the same buffer, the access must be
+/// serialised by the application, for example with a [`crate::Mutex`].
+pub struct StreamBuffer {
+    stream_buffer: FreeRtosStreamBufferHandle,
+}
+
+impl StreamBuffer {
+    /// Create a new stream buffer that can hold `capacity` bytes.
+    ///
+    /// A task blocked on [`receive`](Self::receive) is woken up once at least
+    /// `trigger_level` bytes are available.
+    pub fn new(capacity: usize, trigger_level: usize) -> Result<StreamBuffer, FreeRtosError> {
+        let handle = unsafe { freertos_rs_stream_buffer_create(capacity, trigger_level) };
+
+        if handle.is_null() {
+            return Err(FreeRtosError::OutOfMemory);
+        }
+
+        Ok(StreamBuffer {
+            stream_buffer: handle,
+        })
+    }
+
+    /// # Safety
+    ///
+    /// `handle` must be a valid FreeRTOS stream buffer handle (not a message buffer).
+    #[inline]
+    pub unsafe fn from_raw_handle(handle: FreeRtosStreamBufferHandle) -> Self {
+        Self {
+            stream_buffer: handle,
+        }
+    }
+    #[inline]
+    pub fn raw_handle(&self) -> FreeRtosStreamBufferHandle {
+        self.stream_buffer
+    }
+
+    /// Send bytes to the buffer. Wait for enough space to send all of `data`.
+    ///
+    /// Returns the number of bytes written, which is less than `data.len()` if
+    /// the wait timed out.
+    pub fn send<D: DurationTicks>(&self, data: &[u8], max_wait: D) -> usize {
+        unsafe {
+            freertos_rs_stream_buffer_send(
+                self.stream_buffer,
+                data.as_ptr() as FreeRtosVoidPtr,
+                data.len(),
+                max_wait.to_ticks(),
+            )
+        }
+    }
+
+    /// Send bytes to the buffer, from an interrupt.
+    ///
+    /// Returns the number of bytes that fit into the buffer.
+    pub fn send_from_isr(&self, context: &mut InterruptContext, data: &[u8]) -> usize {
+        unsafe {
+            freertos_rs_stream_buffer_send_isr(
+                self.stream_buffer,
+                data.as_ptr() as FreeRtosVoidPtr,
+                data.len(),
+                context.get_task_field_mut(),
+            )
+        }
+    }
+
+    /// Wait for bytes to be available and copy them into `buffer`.
+    ///
+    /// Returns the number of bytes received, `0` if the wait timed out.
+    pub fn receive<D: DurationTicks>(&self, buffer: &mut [u8], max_wait: D) -> usize {
+        unsafe {
+            freertos_rs_stream_buffer_receive(
+                self.stream_buffer,
+                buffer.as_mut_ptr() as FreeRtosMutVoidPtr,
+                buffer.len(),
+                max_wait.to_ticks(),
+            )
+        }
+    }
+
+    /// Copy the available bytes into `buffer`, from an interrupt.
+    ///
+    /// Returns the number of bytes received.
+    pub fn receive_from_isr(&self, context: &mut InterruptContext, buffer: &mut [u8]) -> usize {
+        unsafe {
+            freertos_rs_stream_buffer_receive_isr(
+                self.stream_buffer,
+                buffer.as_mut_ptr() as FreeRtosMutVoidPtr,
+                buffer.len(),
+                context.get_task_field_mut(),
+            )
+        }
+    }
+
+    /// Get the number of bytes that can be read from the buffer.
+    pub fn bytes_available(&self) -> usize {
+        unsafe { freertos_rs_stream_buffer_bytes_available(self.stream_buffer) }
+    }
+
+    /// Get the number of bytes that can be written to the buffer.
+    pub fn spaces_available(&self) -> usize {
+        unsafe { freertos_rs_stream_buffer_spaces_available(self.stream_buffer) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        unsafe { freertos_rs_stream_buffer_is_empty(self.stream_buffer) == 1 }
+    }
+
+    pub fn is_full(&self) -> bool {
+        unsafe { freertos_rs_stream_buffer_is_full(self.stream_buffer) == 1 }
+    }
+
+    /// Discard the contents of the buffer.
+    ///
+    /// Returns `true` on success, `false` when a task is blocked on the buffer.
+    pub fn reset(&self) -> bool {
+        unsafe { freertos_rs_stream_buffer_reset(self.stream_buffer) == 0 }
+    }
+
+    /// Change the number of bytes that have to be available to unblock a receiving task.
+    ///
+    /// Returns `true` on success, `false` when the level is larger than the buffer.
+    pub fn set_trigger_level(&self, trigger_level: usize) -> bool {
+        unsafe { freertos_rs_stream_buffer_set_trigger_level(self.stream_buffer, trigger_level) == 0 }
+    }
+}
+
+impl Drop for StreamBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            freertos_rs_stream_buffer_delete(self.stream_buffer);
+        }
+    }
+}
@@ -2,8 +2,14 @@ use crate::base::*;
 use crate::isr::*;
 use crate::prelude::v1::*;
 use crate::shim::*;
+#[cfg(feature = "static_alloc")]
+use crate::static_alloc::*;
+#[cfg(feature = "task_local")]
+use crate::task_local::*;
 use crate::units::*;
 use crate::utils::*;
+use core::convert::TryFrom;
+use core::mem::MaybeUninit;
 
 unsafe impl Send for Task {}
 
@@ -56,15 +62,17 @@ impl TaskPriority {
 ///
 /// [`Task::new()`]: struct.Task.html#method.new
 pub struct TaskBuilder {
-    task_name: String,
+    task_name: NameBuffer,
     task_stack_size: u16,
     task_priority: TaskPriority,
+    #[cfg(feature = "task_tag")]
+    task_tag: Option<usize>,
 }
 
 impl TaskBuilder {
-    /// Set the task's name.
+    /// Set the task's name. Names longer than 32 bytes are truncated.
     pub fn name(&mut self, name: &str) -> &mut Self {
-        self.task_name = name.into();
+        self.task_name = NameBuffer::new(name);
         self
     }
 
@@ -80,18 +88,126 @@ impl TaskBuilder {
         self
     }
 
+    /// Set the task's application tag, see [`Task::set_application_tag`].
+    ///
+    /// The tag is set before the new task first runs.
+    #[cfg(feature = "task_tag")]
+    pub fn application_tag(&mut self, tag: usize) -> &mut Self {
+        self.task_tag = Some(tag);
+        self
+    }
+
+    /// Set the task's hook function, see [`Task::set_hook`].
+    #[cfg(feature = "task_tag")]
+    pub fn hook(&mut self, hook: TaskHookFunction) -> &mut Self {
+        self.task_tag = Some(hook as usize);
+        self
+    }
+
     /// Start a new task that can't return a value.
+    #[cfg(feature = "alloc")]
     pub fn start<F>(&self, func: F) -> Result<Task, FreeRtosError>
     where
         F: FnOnce(Task) -> (),
         F: Send + 'static,
     {
-        Task::spawn(
-            &self.task_name,
-            self.task_stack_size,
-            self.task_priority,
-            func,
-        )
+        self.create_tagged(|| {
+            Task::spawn(
+                self.task_name.as_str(),
+                self.task_stack_size,
+                self.task_priority,
+                func,
+            )
+        })
+    }
+
+    /// Start a new task running a closure that may borrow non-`'static` data.
+    ///
+    /// # Safety
+    ///
+    /// The task must finish before any data borrowed by `func` goes out of scope.
+    #[cfg(all(feature = "sync", feature = "alloc"))]
+    pub(crate) unsafe fn start_unchecked<'a>(
+        &self,
+        func: Box<dyn FnOnce(Task) + Send + 'a>,
+    ) -> Result<Task, FreeRtosError> {
+        self.create_tagged(|| {
+            Task::spawn_inner(
+                func,
+                self.task_name.as_str(),
+                self.task_stack_size,
+                self.task_priority,
+            )
+        })
+    }
+
+    /// Start a new task that runs a plain function. Unlike [`start`](Self::start)
+    /// this does not allocate on the Rust heap.
+    pub fn start_fn(&self, func: fn(Task)) -> Result<Task, FreeRtosError> {
+        self.create_tagged(|| {
+            Task::spawn_fn(
+                self.task_name.as_str(),
+                self.task_stack_size,
+                self.task_priority,
+                func,
+            )
+        })
+    }
+
+    /// Start a new task in caller provided storage, without using the FreeRTOS heap.
+    /// The stack size is taken from `stack`, the one set on the builder is ignored.
+    ///
+    /// Sample usage:
+    ///
+    /// ```rust
+    /// # use freertos_rs::*;
+    /// # use core::ptr::addr_of_mut;
+    /// static mut TASK_BUFFER: StaticTaskBuffer = StaticTaskBuffer::new();
+    /// static mut TASK_STACK: StaticTaskStack<256> = StaticTaskStack::new();
+    ///
+    /// fn worker(_task: Task) {
+    ///     loop {
+    ///         CurrentTask::delay(Duration::ms(100));
+    ///     }
+    /// }
+    ///
+    /// unsafe {
+    ///     Task::new()
+    ///         .name("worker")
+    ///         .start_static(&mut *addr_of_mut!(TASK_BUFFER), &mut *addr_of_mut!(TASK_STACK), worker)
+    /// }.unwrap();
+    /// ```
+    #[cfg(feature = "static_alloc")]
+    pub fn start_static<const N: usize>(
+        &self,
+        buffer: &'static mut StaticTaskBuffer,
+        stack: &'static mut StaticTaskStack<N>,
+        func: fn(Task),
+    ) -> Result<Task, FreeRtosError> {
+        self.create_tagged(move || {
+            Task::spawn_static(self.task_name.as_str(), self.task_priority, buffer, stack, func)
+        })
+    }
+
+    fn create_tagged<C>(&self, create: C) -> Result<Task, FreeRtosError>
+    where
+        C: FnOnce() -> Result<Task, FreeRtosError>,
+    {
+        #[cfg(feature = "task_tag")]
+        if let Some(tag) = self.task_tag {
+            // Keep the new task from running before it is tagged.
+            Task::suspend_all();
+            let task = create();
+            if let Ok(task) = &task {
+                task.set_application_tag(tag);
+            }
+            unsafe {
+                Task::resume_all();
+            }
+            return task;
+        }
+
+        create()
     }
 }
 
@@ -99,9 +215,11 @@ impl Task {
     /// Prepare a builder object for the new task.
     pub fn new() -> TaskBuilder {
         TaskBuilder {
-            task_name: "rust_task".into(),
+            task_name: NameBuffer::new("rust_task"),
             task_stack_size: 1024,
             task_priority: TaskPriority(1),
+            #[cfg(feature = "task_tag")]
+            task_tag: None,
         }
     }
 
@@ -133,8 +251,9 @@ impl Task {
         }
     }
 
+    #[cfg(feature = "alloc")]
     unsafe fn spawn_inner<'a>(
-        f: Box<dyn FnOnce(Task)>,
+        f: Box<dyn FnOnce(Task) + 'a>,
         name: &str,
         stack_size: u16,
         priority: TaskPriority,
@@ -176,6 +295,9 @@ impl Task {
                     });
                 }
 
+                #[cfg(feature = "task_local")]
+                drop_task_locals();
+
                 #[cfg(feature = "delete_task")]
                 freertos_rs_delete_task(0 as *const _);
             }
@@ -189,6 +311,7 @@ impl Task {
         Ok(Task { task_handle })
     }
 
+    #[cfg(feature = "alloc")]
     fn spawn<F>(
         name: &str,
         stack_size: u16,
@@ -204,7 +327,169 @@ impl Task {
         }
     }
 
+    fn spawn_fn(
+        name: &str,
+        stack_size: u16,
+        priority: TaskPriority,
+        f: fn(Task),
+    ) -> Result<Task, FreeRtosError> {
+        let (success, task_handle) = unsafe {
+            let name = name.as_bytes();
+            let name_len = name.len();
+            let mut task_handle = core::ptr::null();
+
+            let ret = freertos_rs_spawn_task(
+                fn_thread_start,
+                f as FreeRtosMutVoidPtr,
+                name.as_ptr(),
+                name_len as u8,
+                stack_size,
+                priority.to_freertos(),
+                &mut task_handle,
+            );
+
+            (ret == 0, task_handle)
+        };
+
+        if !success {
+            return Err(FreeRtosError::OutOfMemory);
+        }
+
+        Ok(Task { task_handle })
+    }
+
+    #[cfg(feature = "static_alloc")]
+    fn spawn_static<const N: usize>(
+        name: &str,
+        priority: TaskPriority,
+        buffer: &'static mut StaticTaskBuffer,
+        stack: &'static mut StaticTaskStack<N>,
+        f: fn(Task),
+    ) -> Result<Task, FreeRtosError> {
+        let task_buffer = buffer.as_mut_ptr()?;
+
+        let (success, task_handle) = unsafe {
+            let name = name.as_bytes();
+            let name_len = name.len();
+            let mut task_handle = core::ptr::null();
+
+            let ret = freertos_rs_spawn_task_static(
+                fn_thread_start,
+                f as FreeRtosMutVoidPtr,
+                name.as_ptr(),
+                name_len as u8,
+                N as u32,
+                priority.to_freertos(),
+                stack.as_mut_ptr(),
+                task_buffer,
+                &mut task_handle,
+            );
+
+            (ret == 0, task_handle)
+        };
+
+        if !success {
+            return Err(FreeRtosError::OutOfMemory);
+        }
+
+        Ok(Task { task_handle })
+    }
+
+    /// Suspend this task. It does not run again until it is resumed.
+    pub fn suspend(&self) {
+        unsafe { freertos_rs_suspend_task(self.task_handle) }
+    }
+
+    /// Resume this task if it is suspended.
+    pub fn resume(&self) {
+        unsafe { freertos_rs_resume_task(self.task_handle) }
+    }
+
+    /// Resume this task from an interrupt.
+    pub fn resume_from_isr(&self, context: &mut InterruptContext) {
+        unsafe { freertos_rs_resume_task_isr(self.task_handle, context.get_task_field_mut()) }
+    }
+
+    /// Delete this task and free its resources.
+    ///
+    /// # Safety
+    ///
+    /// The task is stopped wherever it is, so it must not own memory, hold a lock or
+    /// have any other state that would be left behind. All other handles to the task
+    /// become invalid.
+    #[cfg(feature = "delete_task")]
+    pub unsafe fn delete(self) {
+        freertos_rs_delete_task(self.task_handle)
+    }
+
+    /// Set the application tag of this task, a value that FreeRTOS stores with the task
+    /// but does not use itself, for example a trace identifier. Requires
+    /// `configUSE_APPLICATION_TASK_TAG` and the `task_tag` feature.
+    #[cfg(feature = "task_tag")]
+    pub fn set_application_tag(&self, tag: usize) {
+        unsafe { freertos_rs_task_set_application_tag(self.task_handle, tag as FreeRtosMutVoidPtr) }
+    }
+
+    /// Get the application tag of this task, `0` if none is set.
+    #[cfg(feature = "task_tag")]
+    pub fn get_application_tag(&self) -> usize {
+        unsafe { freertos_rs_task_get_application_tag(self.task_handle) as usize }
+    }
+
+    /// Use a hook function as the application tag of this task. It can be called with
+    /// [`call_hook`](Self::call_hook), for example from the `traceTASK_SWITCHED_IN` macro.
+    #[cfg(feature = "task_tag")]
+    pub fn set_hook(&self, hook: TaskHookFunction) {
+        self.set_application_tag(hook as usize)
+    }
+
+    /// Call the hook function of this task with `parameter`. Returns the result of
+    /// the hook, or `0` if no tag is set.
+    ///
+    /// # Safety
+    ///
+    /// The application tag of the task must be `0` or a hook set with
+    /// [`set_hook`](Self::set_hook) or [`TaskBuilder::hook`].
+    #[cfg(feature = "task_tag")]
+    pub unsafe fn call_hook(&self, parameter: FreeRtosMutVoidPtr) -> FreeRtosBaseType {
+        freertos_rs_task_call_application_hook(self.task_handle, parameter)
+    }
+
+    /// Get the priority of this task. Returns `None` if the priority doesn't fit in a
+    /// [`TaskPriority`], which is only possible with `configMAX_PRIORITIES` above 256.
+    pub fn get_priority(&self) -> Option<TaskPriority> {
+        let priority = unsafe { freertos_rs_task_priority_get(self.task_handle) };
+        u8::try_from(priority).ok().map(TaskPriority)
+    }
+
+    /// Change the priority of this task.
+    pub fn set_priority(&self, priority: TaskPriority) {
+        unsafe { freertos_rs_task_priority_set(self.task_handle, priority.to_freertos()) }
+    }
+
+    /// Get the state of this task.
+    pub fn get_state(&self) -> FreeRtosTaskState {
+        match unsafe { freertos_rs_task_get_state(self.task_handle) } {
+            0 => FreeRtosTaskState::Running,
+            1 => FreeRtosTaskState::Ready,
+            2 => FreeRtosTaskState::Blocked,
+            3 => FreeRtosTaskState::Suspended,
+            4 => FreeRtosTaskState::Deleted,
+            _ => FreeRtosTaskState::Invalid,
+        }
+    }
+
+    /// Force this task out of the Blocked state, as if its timeout expired.
+    ///
+    /// Returns `true` on success, `false` when the task was not blocked. Requires
+    /// `INCLUDE_xTaskAbortDelay` and the `abort_delay` feature.
+    #[cfg(feature = "abort_delay")]
+    pub fn abort_delay(&self) -> bool {
+        unsafe { freertos_rs_task_abort_delay(self.task_handle) == 0 }
+    }
+
     /// Get the name of the current task.
+    #[cfg(feature = "alloc")]
     pub fn get_name(&self) -> Result<String, ()> {
         unsafe {
             let name_ptr = freertos_rs_task_get_name(self.task_handle);
@@ -217,6 +502,13 @@ impl Task {
         }
     }
 
+    /// Copy the name of the task into `buffer`, without allocating.
+    pub fn get_name_into<'a>(&self, buffer: &'a mut NameBuffer) -> Result<&'a str, FreeRtosError> {
+        let name = unsafe { str_from_c_string(freertos_rs_task_get_name(self.task_handle))? };
+        *buffer = NameBuffer::new(name);
+        Ok(buffer.as_str())
+    }
+
     /// Try to find the task of the current execution context.
     pub fn current() -> Result<Task, FreeRtosError> {
         unsafe {
@@ -288,6 +580,168 @@ impl Task {
         }
     }
 
+    /// Notify this task and return its notification value from before the notification.
+    pub fn notify_and_query(&self, notification: TaskNotification) -> Result<u32, FreeRtosError> {
+        let mut previous = 0;
+        let n = notification.to_freertos();
+        let r = unsafe { freertos_rs_task_notify_and_query(self.task_handle, n.0, n.1, &mut previous) };
+
+        if r == 0 {
+            Ok(previous)
+        } else {
+            Err(FreeRtosError::QueueFull)
+        }
+    }
+
+    /// Notify this task from an interrupt and return its notification value from
+    /// before the notification.
+    pub fn notify_and_query_from_isr(
+        &self,
+        context: &mut InterruptContext,
+        notification: TaskNotification,
+    ) -> Result<u32, FreeRtosError> {
+        let mut previous = 0;
+        let n = notification.to_freertos();
+        let r = unsafe {
+            freertos_rs_task_notify_and_query_isr(
+                self.task_handle,
+                n.0,
+                n.1,
+                &mut previous,
+                context.get_task_field_mut(),
+            )
+        };
+
+        if r == 0 {
+            Ok(previous)
+        } else {
+            Err(FreeRtosError::QueueFull)
+        }
+    }
+
+    /// Clear a pending notification of this task, without changing its value.
+    ///
+    /// Returns `true` if a notification was pending.
+    pub fn clear_notification_state(&self) -> bool {
+        unsafe { freertos_rs_task_notify_state_clear(self.task_handle) == 0 }
+    }
+
+    /// Clear bits in the notification value of this task and return the value
+    /// from before the bits were cleared.
+    ///
+    /// Unlike the other methods for the notification at index 0, this requires
+    /// FreeRTOS 10.4 or later; older kernels don't provide `ulTaskNotifyValueClear`
+    /// and fail to link.
+    pub fn clear_notification_bits(&self, bits_to_clear: u32) -> u32 {
+        self.clear_notification_bits_indexed(0, bits_to_clear)
+    }
+
+    // Indexed notifications use the array of `configTASK_NOTIFICATION_ARRAY_ENTRIES`
+    // notification values of FreeRTOS 10.4 and later, older kernels fail to link them.
+    // Index 0 is the one used by the methods above.
+
+    /// Notify this task using the notification value at `index`.
+    pub fn notify_indexed(
+        &self,
+        index: u32,
+        notification: TaskNotification,
+    ) -> Result<(), FreeRtosError> {
+        let n = notification.to_freertos();
+        let r = unsafe { freertos_rs_task_notify_indexed(self.task_handle, index, n.0, n.1) };
+
+        if r == 0 {
+            Ok(())
+        } else {
+            Err(FreeRtosError::QueueFull)
+        }
+    }
+
+    /// Notify this task from an interrupt using the notification value at `index`.
+    pub fn notify_indexed_from_isr(
+        &self,
+        context: &mut InterruptContext,
+        index: u32,
+        notification: TaskNotification,
+    ) -> Result<(), FreeRtosError> {
+        let n = notification.to_freertos();
+        let r = unsafe {
+            freertos_rs_task_notify_indexed_isr(
+                self.task_handle,
+                index,
+                n.0,
+                n.1,
+                context.get_task_field_mut(),
+            )
+        };
+
+        if r == 0 {
+            Ok(())
+        } else {
+            Err(FreeRtosError::QueueFull)
+        }
+    }
+
+    /// Notify this task using the notification value at `index` and return that
+    /// value from before the notification.
+    pub fn notify_and_query_indexed(
+        &self,
+        index: u32,
+        notification: TaskNotification,
+    ) -> Result<u32, FreeRtosError> {
+        let mut previous = 0;
+        let n = notification.to_freertos();
+        let r = unsafe {
+            freertos_rs_task_notify_and_query_indexed(self.task_handle, index, n.0, n.1, &mut previous)
+        };
+
+        if r == 0 {
+            Ok(previous)
+        } else {
+            Err(FreeRtosError::QueueFull)
+        }
+    }
+
+    /// Notify this task from an interrupt using the notification value at `index`
+    /// and return that value from before the notification.
+    pub fn notify_and_query_indexed_from_isr(
+        &self,
+        context: &mut InterruptContext,
+        index: u32,
+        notification: TaskNotification,
+    ) -> Result<u32, FreeRtosError> {
+        let mut previous = 0;
+        let n = notification.to_freertos();
+        let r = unsafe {
+            freertos_rs_task_notify_and_query_indexed_isr(
+                self.task_handle,
+                index,
+                n.0,
+                n.1,
+                &mut previous,
+                context.get_task_field_mut(),
+            )
+        };
+
+        if r == 0 {
+            Ok(previous)
+        } else {
+            Err(FreeRtosError::QueueFull)
+        }
+    }
+
+    /// Clear a pending notification at `index` of this task, without changing its value.
+    ///
+    /// Returns `true` if a notification was pending.
+    pub fn clear_notification_state_indexed(&self, index: u32) -> bool {
+        unsafe { freertos_rs_task_notify_state_clear_indexed(self.task_handle, index) == 0 }
+    }
+
+    /// Clear bits in the notification value at `index` of this task and return the
+    /// value from before the bits were cleared.
+    pub fn clear_notification_bits_indexed(&self, index: u32, bits_to_clear: u32) -> u32 {
+        unsafe { freertos_rs_task_notify_value_clear_indexed(self.task_handle, index, bits_to_clear) }
+    }
+
     /// Get the minimum amount of stack that was ever left on this task.
     pub fn get_stack_high_water_mark(&self) -> u32 {
         unsafe { freertos_rs_get_stack_high_water_mark(self.task_handle) as u32 }
@@ -307,6 +761,27 @@ impl Task {
     }
 }
 
+/// Entry point of tasks that run a plain `fn(Task)`, passed as the task parameter.
+extern "C" fn fn_thread_start(main: FreeRtosMutVoidPtr) -> FreeRtosMutVoidPtr {
+    unsafe {
+        let f: fn(Task) = mem::transmute(main);
+        f(Task {
+            task_handle: freertos_rs_get_current_task(),
+        });
+
+        #[cfg(feature = "task_local")]
+        drop_task_locals();
+
+        #[cfg(feature = "delete_task")]
+        freertos_rs_delete_task(0 as *const _);
+    }
+
+    #[cfg(feature = "delete_task")]
+    return 0 as *mut _;
+    #[cfg(not(feature = "delete_task"))]
+    panic!("Not allowed to quit the task!");
+}
+
 /// Helper methods to be performed on the task that is currently executing.
 pub struct CurrentTask;
 
@@ -329,18 +804,53 @@ impl CurrentTask {
         unsafe { freertos_rs_task_notify_take(if clear { 1 } else { 0 }, wait_for.to_ticks()) }
     }
 
+    /// Take the notification at `index` and either clear the notification value or decrement it by one.
+    pub fn take_notification_indexed<D: DurationTicks>(index: u32, clear: bool, wait_for: D) -> u32 {
+        unsafe {
+            freertos_rs_task_notify_take_indexed(index, if clear { 1 } else { 0 }, wait_for.to_ticks())
+        }
+    }
+
+    /// Wait for a notification to be posted to the notification value at `index` of
+    /// the current task.
+    pub fn wait_for_notification_indexed<D: DurationTicks>(
+        index: u32,
+        clear_bits_enter: u32,
+        clear_bits_exit: u32,
+        wait_for: D,
+    ) -> Result<u32, FreeRtosError> {
+        let mut val = 0;
+        let r = unsafe {
+            freertos_rs_task_notify_wait_indexed(
+                index,
+                clear_bits_enter,
+                clear_bits_exit,
+                &mut val as *mut _,
+                wait_for.to_ticks(),
+            )
+        };
+
+        if r == 0 {
+            Ok(val)
+        } else {
+            Err(FreeRtosError::Timeout)
+        }
+    }
+
     /// Get the minimum amount of stack that was ever left on the current task.
     pub fn get_stack_high_water_mark() -> u32 {
         unsafe { freertos_rs_get_stack_high_water_mark(0 as FreeRtosTaskHandle) as u32 }
     }
 }
 
+#[cfg(feature = "alloc")]
 #[derive(Debug)]
 pub struct FreeRtosSystemState {
     pub tasks: Vec<FreeRtosTaskStatus>,
     pub total_run_time: u32,
 }
 
+#[cfg(feature = "alloc")]
 impl fmt::Display for FreeRtosSystemState {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         fmt.write_str("FreeRTOS tasks\r\n")?;
@@ -385,6 +895,7 @@ impl fmt::Display for FreeRtosSystemState {
     }
 }
 
+#[cfg(feature = "alloc")]
 #[derive(Debug)]
 pub struct FreeRtosTaskStatus {
     pub task: Task,
@@ -442,6 +953,7 @@ impl FreeRtosUtils {
         unsafe { freertos_rs_get_number_of_tasks() as usize }
     }
 
+    #[cfg(feature = "alloc")]
     pub fn get_all_tasks(tasks_len: Option<usize>) -> FreeRtosSystemState {
         let tasks_len = tasks_len.unwrap_or(Self::get_number_of_tasks());
         let mut tasks = Vec::with_capacity(tasks_len as usize);
@@ -478,4 +990,37 @@ impl FreeRtosUtils {
             total_run_time: total_run_time,
         }
     }
+
+    /// Fill `tasks` with the state of the tasks in the system, without allocating.
+    ///
+    /// Returns the filled part of `tasks` and the total run time. If `tasks` is
+    /// shorter than [`get_number_of_tasks`](Self::get_number_of_tasks), nothing is filled.
+    pub fn get_all_tasks_into(
+        tasks: &mut [MaybeUninit<FreeRtosTaskStatusFfi>],
+    ) -> (&mut [FreeRtosTaskStatusFfi], u32) {
+        let mut total_run_time = 0;
+
+        unsafe {
+            let filled = freertos_rs_get_system_state(
+                tasks.as_mut_ptr() as *mut FreeRtosTaskStatusFfi,
+                tasks.len() as FreeRtosUBaseType,
+                &mut total_run_time,
+            );
+            let tasks = core::slice::from_raw_parts_mut(
+                tasks.as_mut_ptr() as *mut FreeRtosTaskStatusFfi,
+                filled as usize,
+            );
+
+            (tasks, total_run_time)
+        }
+    }
+}
+
+impl FreeRtosTaskStatusFfi {
+    /// Copy the task's name. The name is stored in the task, so it must be copied while
+    /// the task exists, before it can be deleted.
+    pub fn name(&self) -> Result<NameBuffer, FreeRtosError> {
+        let name = unsafe { str_from_c_string(self.task_name)? };
+        Ok(NameBuffer::new(name))
+    }
 }
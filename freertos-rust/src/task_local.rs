@@ -0,0 +1,172 @@
+use crate::base::*;
+use crate::prelude::v1::*;
+use crate::shim::*;
+
+use core::ptr;
+
+struct LocalValue {
+    key: usize,
+    value: *mut u8,
+    drop_fn: unsafe fn(*mut u8),
+}
+
+type LocalValues = Vec<LocalValue>;
+
+/// Declare task-local values, which are initialised on first use in every task.
+///
+/// Sample usage:
+///
+/// ```rust
+/// # use freertos_rs::*;
+/// # use core::cell::Cell;
+/// task_local! {
+///     static REQUEST_ID: Cell<u32> = Cell::new(0);
+/// }
+///
+/// REQUEST_ID.with(|id| id.set(id.get() + 1));
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr; $($rest:tt)*) => {
+        $crate::task_local!($(#[$attr])* $vis static $name: $t = $init);
+        $crate::task_local!($($rest)*);
+    };
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $t:ty = $init:expr) => {
+        $(#[$attr])*
+        $vis static $name: $crate::TaskLocalKey<$t> = $crate::TaskLocalKey::new({
+            fn __init() -> $t {
+                $init
+            }
+            __init
+        });
+    };
+}
+
+/// A key for a value that every task has its own copy of, declared with [`task_local!`].
+///
+/// Requires `configNUM_THREAD_LOCAL_STORAGE_POINTERS` to be at least 1, the shim fails
+/// to compile otherwise when it is built with `freertos-cargo-build`.
+///
+/// The values live in one thread local storage slot of each task, slot 0 unless
+/// `FREERTOS_RS_TASK_LOCAL_INDEX` is defined in `FreeRTOSConfig.h`. The slot has to be
+/// below `configNUM_THREAD_LOCAL_STORAGE_POINTERS`, which the shim checks. They are dropped when
+/// a task started with a [`crate::TaskBuilder`] returns from its closure or function;
+/// the values of other tasks are leaked.
+pub struct TaskLocalKey<T: 'static> {
+    init: fn() -> T,
+}
+
+impl<T: 'static> TaskLocalKey<T> {
+    #[doc(hidden)]
+    pub const fn new(init: fn() -> T) -> TaskLocalKey<T> {
+        TaskLocalKey { init }
+    }
+
+    /// Get a reference to the current task's value, initialising it first if needed.
+    ///
+    /// Panics if the initialiser uses this key itself. If the initialiser panics, the
+    /// value stays uninitialised and the next use runs the initialiser again.
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        let key = self as *const _ as usize;
+
+        let value = match unsafe { find(key) } {
+            Some(value) if value.is_null() => {
+                panic!("task-local value used by its own initialiser");
+            }
+            Some(value) => value,
+            None => {
+                // A null value marks the key as being initialised. The initialiser may use
+                // other task-locals, so look the entry up again afterwards.
+                unsafe {
+                    current_values().push(LocalValue {
+                        key,
+                        value: ptr::null_mut(),
+                        drop_fn: drop_value::<T>,
+                    });
+                }
+                let initialising = Initialising { key };
+                let value = Box::into_raw(Box::new((self.init)())) as *mut u8;
+                unsafe {
+                    if let Some(v) = current_values().iter_mut().find(|v| v.key == key) {
+                        v.value = value;
+                    }
+                }
+                drop(initialising);
+                value
+            }
+        };
+
+        f(unsafe { &*(value as *const T) })
+    }
+}
+
+/// Removes the null value of a key again if its initialiser panics.
+struct Initialising {
+    key: usize,
+}
+
+impl Drop for Initialising {
+    fn drop(&mut self) {
+        unsafe {
+            current_values().retain(|v| v.key != self.key || !v.value.is_null());
+        }
+    }
+}
+
+unsafe fn drop_value<T>(value: *mut u8) {
+    drop(Box::from_raw(value as *mut T));
+}
+
+/// Thread local storage slot that holds the task-local values of a task.
+fn tls_index() -> FreeRtosBaseType {
+    unsafe { freertos_rs_task_local_index() }
+}
+
+unsafe fn find(key: usize) -> Option<*mut u8> {
+    let values = freertos_rs_task_get_tls_pointer(ptr::null(), tls_index()) as *mut LocalValues;
+
+    if values.is_null() {
+        return None;
+    }
+
+    (*values).iter().find(|v| v.key == key).map(|v| v.value)
+}
+
+/// Get the values of the current task, creating the list if needed. The reference
+/// must not be held while running user code.
+unsafe fn current_values<'a>() -> &'a mut LocalValues {
+    let mut values = freertos_rs_task_get_tls_pointer(ptr::null(), tls_index()) as *mut LocalValues;
+
+    if values.is_null() {
+        values = Box::into_raw(Box::new(LocalValues::new()));
+        freertos_rs_task_set_tls_pointer(ptr::null(), tls_index(), values as FreeRtosMutVoidPtr);
+    }
+
+    &mut *values
+}
+
+/// Drop all task-local values of the current task. Called when a task finishes.
+pub(crate) fn drop_task_locals() {
+    unsafe {
+        // Destructors may initialise other task-locals, so repeat until none are left.
+        loop {
+            let values = freertos_rs_task_get_tls_pointer(ptr::null(), tls_index()) as *mut LocalValues;
+
+            if values.is_null() {
+                break;
+            }
+
+            freertos_rs_task_set_tls_pointer(ptr::null(), tls_index(), ptr::null_mut());
+
+            for v in Box::from_raw(values).into_iter() {
+                if !v.value.is_null() {
+                    (v.drop_fn)(v.value);
+                }
+            }
+        }
+    }
+}
@@ -2,7 +2,12 @@ use crate::InterruptContext;
 use crate::base::*;
 use crate::prelude::v1::*;
 use crate::shim::*;
+#[cfg(feature = "static_alloc")]
+use crate::static_alloc::*;
 use crate::units::*;
+use crate::utils::*;
+
+use core::ptr;
 
 unsafe impl Send for Timer {}
 unsafe impl Sync for Timer {}
@@ -14,19 +19,44 @@ unsafe impl Sync for Timer {}
 /// for that queue to get unblocked.
 pub struct Timer {
     handle: FreeRtosTimerHandle,
+    owned: TimerOwned,
+}
+
+/// Memory owned by a timer, freed by the timer daemon task after the timer is deleted.
+#[derive(Clone, Copy)]
+enum TimerOwned {
+    /// The ID points to the boxed [`TimerData`].
+    #[cfg(feature = "alloc")]
+    Data,
+    /// The name was copied to the FreeRTOS heap, the ID is a plain function pointer.
+    Name,
+    /// Static timers only own the timer itself.
+    #[cfg(feature = "static_alloc")]
+    Timer,
+    /// Timers wrapped around a raw handle and borrowed timers don't own anything, not
+    /// even the timer. Dropping them doesn't delete the timer.
+    Nothing,
+}
+
+/// Owned by a timer with a boxed callback, its ID points here.
+#[cfg(feature = "alloc")]
+struct TimerData<'a> {
+    /// FreeRTOS keeps a pointer to the name instead of copying it.
+    name: NameBuffer,
+    callback: Box<dyn FnMut(&TimerRef) + Send + 'a>,
 }
 
 /// Helper builder for a new software timer.
 pub struct TimerBuilder<D: DurationTicks> {
-    name: String,
+    name: NameBuffer,
     period: D,
     auto_reload: bool,
 }
 
 impl<D: DurationTicks> TimerBuilder<D> {
-    /// Set the name of the timer.
+    /// Set the name of the timer. Names longer than 32 bytes are truncated.
     pub fn set_name(&mut self, name: &str) -> &mut Self {
-        self.name = name.into();
+        self.name = NameBuffer::new(name);
         self
     }
 
@@ -44,16 +74,55 @@ impl<D: DurationTicks> TimerBuilder<D> {
 
     /// Try to create the new timer.
     ///
+    /// The callback is only ever called by the timer daemon task, one call at a time,
+    /// so it can keep mutable state.
+    ///
     /// Note that the newly created timer must be started.
+    #[cfg(feature = "alloc")]
     pub fn create<F>(&self, callback: F) -> Result<Timer, FreeRtosError>
     where
-        F: Fn(&Timer) -> (),
+        F: FnMut(&TimerRef),
         F: Send + 'static,
     {
         Timer::spawn(
-            self.name.as_str(),
+            &self.name,
+            self.period.to_ticks(),
+            self.auto_reload,
+            callback,
+        )
+    }
+
+    /// Try to create the new timer with a plain function as its callback. Only the timer
+    /// and its name are allocated, on the FreeRTOS heap, so this works without the
+    /// `alloc` feature.
+    ///
+    /// The name is freed by the timer daemon task after the timer is deleted, which
+    /// requires `INCLUDE_xTimerPendFunctionCall`. Without it the name is leaked.
+    ///
+    /// Note that the newly created timer must be started.
+    pub fn create_fn(&self, callback: fn(&TimerRef)) -> Result<Timer, FreeRtosError> {
+        Timer::spawn_fn(
+            &self.name,
+            self.period.to_ticks(),
+            self.auto_reload,
+            callback,
+        )
+    }
+
+    /// Try to create the new timer in caller provided storage, without using the FreeRTOS heap.
+    ///
+    /// Note that the newly created timer must be started.
+    #[cfg(feature = "static_alloc")]
+    pub fn create_static(
+        &self,
+        buffer: &'static mut StaticTimerBuffer,
+        callback: fn(&TimerRef),
+    ) -> Result<Timer, FreeRtosError> {
+        Timer::spawn_static(
+            &self.name,
             self.period.to_ticks(),
             self.auto_reload,
+            buffer,
             callback,
         )
     }
@@ -63,7 +132,7 @@ impl Timer {
     /// Create a new timer builder.
     pub fn new<D: DurationTicks>(period: D) -> TimerBuilder<D> {
         TimerBuilder {
-            name: "timer".into(),
+            name: NameBuffer::new("timer"),
             period: period,
             auto_reload: true,
         }
@@ -71,81 +140,148 @@ impl Timer {
 
     /// Create a timer from a raw handle.
     ///
+    /// The returned value doesn't own the timer: dropping it doesn't delete the timer,
+    /// and [`Timer::delete`] deletes it without freeing its callback or name.
+    ///
     /// # Safety
     ///
     /// `handle` must be a valid FreeRTOS timer handle.
     #[inline]
     pub unsafe fn from_raw_handle(handle: FreeRtosTimerHandle) -> Self {
-        Self { handle }
+        Self {
+            handle,
+            owned: TimerOwned::Nothing,
+        }
     }
     #[inline]
     pub fn raw_handle(&self) -> FreeRtosTimerHandle {
         self.handle
     }
 
+    #[cfg(feature = "alloc")]
     unsafe fn spawn_inner<'a>(
-        name: &str,
+        name: &NameBuffer,
         period_ticks: FreeRtosTickType,
         auto_reload: bool,
-        callback: Box<dyn Fn(&Timer) + Send + 'a>,
+        callback: Box<dyn FnMut(&TimerRef) + Send + 'a>,
     ) -> Result<Timer, FreeRtosError> {
-        let f = Box::new(callback);
-        let param_ptr = &*f as *const _ as *mut _;
-
-        let (success, timer_handle) = {
-            let name = name.as_bytes();
-            let name_len = name.len();
-
-            let ret = freertos_rs_timer_create(
-                name.as_ptr(),
-                name_len as u8,
-                period_ticks,
-                if auto_reload { 1 } else { 0 },
-                param_ptr,
-                timer_callback,
-            );
+        // The callback is called through the ID, so the pointer must allow writes.
+        let data = Box::into_raw(Box::new(TimerData {
+            name: *name,
+            callback,
+        }));
 
-            ((ret as usize) != 0, ret)
-        };
+        let timer_handle = freertos_rs_timer_create(
+            (*data).name.as_c_str_ptr(),
+            period_ticks,
+            if auto_reload { 1 } else { 0 },
+            data as FreeRtosVoidPtr,
+            timer_callback,
+        );
 
-        if success {
-            mem::forget(f);
-        } else {
+        if timer_handle.is_null() {
+            drop(Box::from_raw(data));
             return Err(FreeRtosError::OutOfMemory);
         }
 
         extern "C" fn timer_callback(handle: FreeRtosTimerHandle) -> () {
             unsafe {
-                {
-                    let timer = Timer { handle };
-                    if let Ok(callback_ptr) = timer.get_id() {
-                        let b = Box::from_raw(callback_ptr as *mut Box<dyn Fn(&Timer)>);
-                        b(&timer);
-                        let _ = Box::into_raw(b);
-                    }
-                    mem::forget(timer);
-                }
+                let timer = TimerRef { handle };
+                let data_ptr = freertos_rs_timer_get_id(handle) as *mut TimerData;
+                // Only the daemon task calls the callback, never concurrently. Other
+                // tasks may read the name, so only the callback is borrowed mutably.
+                let callback = &mut *ptr::addr_of_mut!((*data_ptr).callback);
+                callback(&timer);
             }
         }
 
         Ok(Timer {
             handle: timer_handle as *const _,
+            owned: TimerOwned::Data,
         })
     }
 
+    #[cfg(feature = "alloc")]
     fn spawn<F>(
-        name: &str,
+        name: &NameBuffer,
         period_tick: FreeRtosTickType,
         auto_reload: bool,
         callback: F,
     ) -> Result<Timer, FreeRtosError>
     where
-        F: Fn(&Timer) -> (),
+        F: FnMut(&TimerRef),
         F: Send + 'static,
     {
         unsafe { Timer::spawn_inner(name, period_tick, auto_reload, Box::new(callback)) }
     }
 
+    fn spawn_fn(
+        name: &NameBuffer,
+        period_ticks: FreeRtosTickType,
+        auto_reload: bool,
+        callback: fn(&TimerRef),
+    ) -> Result<Timer, FreeRtosError> {
+        // FreeRTOS keeps a pointer to the name instead of copying it.
+        let name = name.as_bytes_with_nul();
+        let name_ptr = unsafe { freertos_rs_pvPortMalloc(name.len() as FreeRtosUBaseType) } as *mut u8;
+        if name_ptr.is_null() {
+            return Err(FreeRtosError::OutOfMemory);
+        }
+
+        let timer_handle = unsafe {
+            ptr::copy_nonoverlapping(name.as_ptr(), name_ptr, name.len());
+
+            freertos_rs_timer_create(
+                name_ptr,
+                period_ticks,
+                if auto_reload { 1 } else { 0 },
+                callback as FreeRtosVoidPtr,
+                fn_timer_callback,
+            )
+        };
+
+        if timer_handle.is_null() {
+            unsafe { freertos_rs_vPortFree(name_ptr as FreeRtosVoidPtr) };
+            return Err(FreeRtosError::OutOfMemory);
+        }
+
+        Ok(Timer {
+            handle: timer_handle,
+            owned: TimerOwned::Name,
+        })
+    }
+
+    #[cfg(feature = "static_alloc")]
+    fn spawn_static(
+        name: &NameBuffer,
+        period_ticks: FreeRtosTickType,
+        auto_reload: bool,
+        buffer: &'static mut StaticTimerBuffer,
+        callback: fn(&TimerRef),
+    ) -> Result<Timer, FreeRtosError> {
+        let (name, timer_buffer) = buffer.prepare(name)?;
+
+        let timer_handle = unsafe {
+            freertos_rs_timer_create_static(
+                name,
+                period_ticks,
+                if auto_reload { 1 } else { 0 },
+                callback as FreeRtosVoidPtr,
+                fn_timer_callback,
+                timer_buffer,
+            )
+        };
+
+        if timer_handle == 0 as *const _ {
+            return Err(FreeRtosError::OutOfMemory);
+        }
+
+        Ok(Timer {
+            handle: timer_handle,
+            owned: TimerOwned::Timer,
+        })
+    }
+
     /// Start the timer.
     pub fn start<D: DurationTicks>(&self, block_time: D) -> Result<(), FreeRtosError> {
         unsafe {
@@ -199,6 +335,87 @@ impl Timer {
         }
     }
 
+    /// Restart the timer, so it expires one period from now. Starts the timer if it
+    /// is not active.
+    pub fn reset<D: DurationTicks>(&self, block_time: D) -> Result<(), FreeRtosError> {
+        unsafe {
+            if freertos_rs_timer_reset(self.handle, block_time.to_ticks()) == 0 {
+                Ok(())
+            } else {
+                Err(FreeRtosError::Timeout)
+            }
+        }
+    }
+
+    /// Restart the timer from an interrupt.
+    pub fn reset_from_isr(&self, context: &mut InterruptContext) -> Result<(), FreeRtosError> {
+        unsafe {
+            if freertos_rs_timer_reset_from_isr(self.handle, context.get_task_field_mut()) == 0 {
+                Ok(())
+            } else {
+                Err(FreeRtosError::QueueSendTimeout)
+            }
+        }
+    }
+
+    /// Stop the timer from an interrupt.
+    pub fn stop_from_isr(&self, context: &mut InterruptContext) -> Result<(), FreeRtosError> {
+        unsafe {
+            if freertos_rs_timer_stop_from_isr(self.handle, context.get_task_field_mut()) == 0 {
+                Ok(())
+            } else {
+                Err(FreeRtosError::QueueSendTimeout)
+            }
+        }
+    }
+
+    /// Change the period of the timer from an interrupt.
+    pub fn change_period_from_isr<D: DurationTicks>(
+        &self,
+        context: &mut InterruptContext,
+        new_period: D,
+    ) -> Result<(), FreeRtosError> {
+        unsafe {
+            if freertos_rs_timer_change_period_from_isr(
+                self.handle,
+                new_period.to_ticks(),
+                context.get_task_field_mut(),
+            ) == 0
+            {
+                Ok(())
+            } else {
+                Err(FreeRtosError::QueueSendTimeout)
+            }
+        }
+    }
+
+    /// Check whether the timer is running. Commands that are still queued for the
+    /// timer daemon task are not taken into account.
+    pub fn is_active(&self) -> bool {
+        unsafe { freertos_rs_timer_is_active(self.handle) == 1 }
+    }
+
+    /// Get the period of the timer.
+    pub fn get_period(&self) -> Duration {
+        Duration::ticks(unsafe { freertos_rs_timer_get_period(self.handle) })
+    }
+
+    /// Get the tick count at which the timer expires next. Only meaningful while the
+    /// timer is active.
+    pub fn get_expiry_time(&self) -> FreeRtosTickType {
+        unsafe { freertos_rs_timer_get_expiry_time(self.handle) }
+    }
+
+    /// Switch between an auto-reload and a one-shot timer.
+    pub fn set_reload_mode(&self, auto_reload: bool) {
+        unsafe { freertos_rs_timer_set_reload_mode(self.handle, if auto_reload { 1 } else { 0 }) }
+    }
+
+    /// Get the name the timer was created with.
+    pub fn get_name(&self) -> Result<&str, FreeRtosError> {
+        unsafe { timer_name(self.handle) }
+    }
+
     /// Detach this timer from Rust's memory management. The timer will still be active and
     /// will consume the memory.
     ///
@@ -209,22 +426,222 @@ impl Timer {
         mem::forget(self);
     }
 
-    fn get_id(&self) -> Result<FreeRtosVoidPtr, FreeRtosError> {
-        unsafe { Ok(freertos_rs_timer_get_id(self.handle)) }
+    /// Delete the timer. The timer is handed back if the delete command could not be
+    /// queued within `block_time`.
+    ///
+    /// The callback or name of the timer is freed by the timer daemon task after it
+    /// deleted the timer. That requires `INCLUDE_xTimerPendFunctionCall`, without it
+    /// they are leaked.
+    pub fn delete<D: DurationTicks>(self, block_time: D) -> Result<(), (Timer, FreeRtosError)> {
+        match self.delete_inner(block_time.to_ticks()) {
+            Ok(()) => {
+                mem::forget(self);
+                Ok(())
+            }
+            Err(e) => Err((self, e)),
+        }
+    }
+
+    fn delete_inner(&self, block_time: FreeRtosTickType) -> Result<(), FreeRtosError> {
+        // The ID and name can't be read anymore once the daemon task deleted the timer.
+        let owned: Option<(FreeRtosPendedFunction, FreeRtosVoidPtr)> = unsafe {
+            match self.owned {
+                #[cfg(feature = "alloc")]
+                TimerOwned::Data => Some((free_timer_data, freertos_rs_timer_get_id(self.handle))),
+                TimerOwned::Name => Some((free_timer_name, freertos_rs_timer_get_name(self.handle) as _)),
+                #[cfg(feature = "static_alloc")]
+                TimerOwned::Timer => None,
+                TimerOwned::Nothing => None,
+            }
+        };
+
+        if unsafe { freertos_rs_timer_delete(self.handle, block_time) } != 0 {
+            return Err(FreeRtosError::Timeout);
+        }
+
+        // Queued behind the delete command, so the callback is not running anymore and
+        // never called again when it is freed. If it can't be queued it is leaked.
+        if let Some((free, owned_ptr)) = owned {
+            if !owned_ptr.is_null() {
+                unsafe {
+                    freertos_rs_pend_function_call(free, owned_ptr as *mut _, 0, block_time);
+                }
+            }
+        }
+
+        Ok(())
     }
 }
 
 impl Drop for Timer {
-    #[allow(unused_must_use)]
+    /// Delete the timer, waiting up to one second to queue the command. Use
+    /// [`Timer::delete`] to choose the waiting time and handle failures.
+    ///
+    /// Timers created with [`Timer::from_raw_handle`] are not deleted.
     fn drop(&mut self) {
+        if let TimerOwned::Nothing = self.owned {
+            return;
+        }
+        let _ = self.delete_inner(Duration::ms(1000).to_ticks());
+    }
+}
+
+/// The name lives as long as the timer.
+unsafe fn timer_name<'a>(handle: FreeRtosTimerHandle) -> Result<&'a str, FreeRtosError> {
+    let name_ptr = freertos_rs_timer_get_name(handle);
+    if name_ptr.is_null() {
+        return Err(FreeRtosError::StringConversionError);
+    }
+    str_from_c_string(name_ptr)
+}
+
+#[cfg(feature = "alloc")]
+extern "C" fn free_timer_data(data_ptr: FreeRtosMutVoidPtr, _: u32) {
+    unsafe { drop(Box::from_raw(data_ptr as *mut TimerData)) }
+}
+
+extern "C" fn free_timer_name(name_ptr: FreeRtosMutVoidPtr, _: u32) {
+    unsafe { freertos_rs_vPortFree(name_ptr) }
+}
+
+/// Callback of timers whose ID is a plain function pointer.
+extern "C" fn fn_timer_callback(handle: FreeRtosTimerHandle) {
+    unsafe {
+        let timer = TimerRef { handle };
+        let callback: fn(&TimerRef) = mem::transmute(freertos_rs_timer_get_id(handle));
+        callback(&timer);
+    }
+}
+
+/// The timer whose callback is running, passed to the callback.
+///
+/// Callbacks run on the timer daemon task, which must never block. All commands are
+/// therefore sent with a block time of zero; they only fail if the timer command
+/// queue is full.
+#[derive(Debug)]
+pub struct TimerRef {
+    handle: FreeRtosTimerHandle,
+}
+
+impl TimerRef {
+    #[inline]
+    pub fn raw_handle(&self) -> FreeRtosTimerHandle {
+        self.handle
+    }
+
+    /// Start the timer.
+    pub fn start(&self) -> Result<(), FreeRtosError> {
+        self.timer().start(Duration::zero())
+    }
+
+    /// Stop the timer, for example to end an auto-reload timer from its callback.
+    pub fn stop(&self) -> Result<(), FreeRtosError> {
+        self.timer().stop(Duration::zero())
+    }
+
+    /// Restart the timer, so it expires one period from now.
+    pub fn reset(&self) -> Result<(), FreeRtosError> {
+        self.timer().reset(Duration::zero())
+    }
+
+    /// Change the period of the timer. Also starts the timer if it is not active.
+    pub fn change_period<D: DurationTicks>(&self, new_period: D) -> Result<(), FreeRtosError> {
+        self.timer()
+            .change_period(Duration::zero(), Duration::ticks(new_period.to_ticks()))
+    }
+
+    /// Get the period of the timer.
+    pub fn get_period(&self) -> Duration {
+        self.timer().get_period()
+    }
+
+    /// Get the name the timer was created with.
+    pub fn get_name(&self) -> Result<&str, FreeRtosError> {
+        unsafe { timer_name(self.handle) }
+    }
+
+    /// Borrow the timer without taking ownership, so it is never deleted from here.
+    fn timer(&self) -> mem::ManuallyDrop<Timer> {
+        mem::ManuallyDrop::new(Timer {
+            handle: self.handle,
+            owned: TimerOwned::Nothing,
+        })
+    }
+}
+
+/// Deferred function calls on the timer daemon task, which processes all timer commands.
+///
+/// Interrupts can hand work to the daemon task instead of waking a task of their own.
+/// Requires `INCLUDE_xTimerPendFunctionCall`.
+///
+/// Sample usage:
+///
+/// ```rust
+/// # use freertos_rs::*;
+/// // in the interrupt handler
+/// # let mut context = InterruptContext::new();
+/// fn handle_status(status: u32) {
+///     // runs on the timer daemon task
+///     # let _ = status;
+/// }
+///
+/// TimerDaemon::pend_from_isr(&mut context, handle_status, 0x42).unwrap();
+/// ```
+pub struct TimerDaemon;
+
+impl TimerDaemon {
+    /// Run `func` on the timer daemon task. Waits up to `block_time` for space in the
+    /// timer command queue.
+    #[cfg(feature = "alloc")]
+    pub fn pend<F, D>(func: F, block_time: D) -> Result<(), FreeRtosError>
+    where
+        F: FnOnce() + Send + 'static,
+        D: DurationTicks,
+    {
+        extern "C" fn run_boxed<F: FnOnce()>(func: FreeRtosMutVoidPtr, _: u32) {
+            let func = unsafe { Box::from_raw(func as *mut F) };
+            func();
+        }
+
+        let func = Box::into_raw(Box::new(func));
+
         unsafe {
-            if let Ok(callback_ptr) = self.get_id() {
-                // free the memory
-                Box::from_raw(callback_ptr as *mut Box<dyn Fn(Timer)>);
+            if freertos_rs_pend_function_call(run_boxed::<F>, func as *mut _, 0, block_time.to_ticks()) != 0 {
+                drop(Box::from_raw(func));
+                return Err(FreeRtosError::Timeout);
             }
+        }
+
+        Ok(())
+    }
+
+    /// Run `func` with `parameter` on the timer daemon task, from an interrupt.
+    ///
+    /// No memory is allocated, so only a plain function can be passed. FreeRTOS hands a
+    /// pointer and a 32 bit value to the daemon task; the pointer holds `func`.
+    pub fn pend_from_isr(
+        context: &mut InterruptContext,
+        func: fn(u32),
+        parameter: u32,
+    ) -> Result<(), FreeRtosError> {
+        extern "C" fn run_fn(func: FreeRtosMutVoidPtr, parameter: u32) {
+            let func: fn(u32) = unsafe { mem::transmute(func) };
+            func(parameter);
+        }
 
-            // todo: configurable timeout?
-            freertos_rs_timer_delete(self.handle, Duration::ms(1000).to_ticks());
+        let r = unsafe {
+            freertos_rs_pend_function_call_isr(
+                run_fn,
+                func as FreeRtosMutVoidPtr,
+                parameter,
+                context.get_task_field_mut(),
+            )
+        };
+
+        if r == 0 {
+            Ok(())
+        } else {
+            Err(FreeRtosError::QueueFull)
         }
     }
 }
@@ -31,10 +31,13 @@ pub fn shim_sanity_check() -> Result<(), TypeSizeError> {
         (23, mem::size_of::<FreeRtosTaskFunction>()),
         (24, mem::size_of::<FreeRtosTimerHandle>()),
         (25, mem::size_of::<FreeRtosTimerCallback>()),
+        (26, mem::size_of::<FreeRtosStreamBufferHandle>()),
+        (27, mem::size_of::<FreeRtosMessageBufferHandle>()),
         (30, mem::size_of::<FreeRtosTaskStatusFfi>()),
         (31, mem::size_of::<FreeRtosTaskState>()),
         (32, mem::size_of::<FreeRtosUnsignedLong>()),
         (33, mem::size_of::<FreeRtosUnsignedShort>()),
+        (34, mem::size_of::<FreeRtosStackType>()),
     ];
 
     for check in &checks {
@@ -62,3 +65,56 @@ pub unsafe fn str_from_c_string<'a>(str: *const u8) -> Result<&'a str, FreeRtosE
         Err(_) => Err(FreeRtosError::StringConversionError),
     }
 }
+
+/// Maximum number of bytes kept for task and timer names. FreeRTOS truncates them
+/// further to `configMAX_TASK_NAME_LEN`.
+#[cfg(any(feature = "time", feature = "sync"))]
+pub const NAME_BUFFER_LEN: usize = 32;
+
+/// A task or timer name that is stored inline instead of on the heap.
+#[cfg(any(feature = "time", feature = "sync"))]
+#[derive(Debug, Copy, Clone)]
+pub struct NameBuffer {
+    /// Always nul-terminated, for FreeRTOS APIs that keep a pointer to the name.
+    buffer: [u8; NAME_BUFFER_LEN + 1],
+    len: usize,
+}
+
+#[cfg(any(feature = "time", feature = "sync"))]
+impl NameBuffer {
+    /// Copy `name` into a new buffer, truncating it at a character boundary if it is too long.
+    pub fn new(name: &str) -> Self {
+        let mut len = name.len().min(NAME_BUFFER_LEN);
+        while !name.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        let mut buffer = [0; NAME_BUFFER_LEN + 1];
+        buffer[..len].copy_from_slice(&name.as_bytes()[..len]);
+
+        NameBuffer { buffer, len }
+    }
+
+    #[cfg(all(feature = "time", feature = "static_alloc"))]
+    pub(crate) const fn empty() -> Self {
+        NameBuffer {
+            buffer: [0; NAME_BUFFER_LEN + 1],
+            len: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Only ever filled from a `&str` and cut at a character boundary.
+        unsafe { core::str::from_utf8_unchecked(&self.buffer[..self.len]) }
+    }
+
+    #[cfg(all(feature = "time", any(feature = "alloc", feature = "static_alloc")))]
+    pub(crate) fn as_c_str_ptr(&self) -> FreeRtosCharPtr {
+        self.buffer.as_ptr()
+    }
+
+    #[cfg(feature = "time")]
+    pub(crate) fn as_bytes_with_nul(&self) -> &[u8] {
+        &self.buffer[..self.len + 1]
+    }
+}
@@ -0,0 +1,201 @@
+use crate::critical::*;
+use crate::executor::{pend_wake_from_isr, wake_from_isr};
+use crate::isr::*;
+use crate::prelude::v1::*;
+
+use core::marker::PhantomPinned;
+use core::pin::Pin;
+use core::ptr;
+use core::task::Waker;
+
+/// Holds the wakers of the futures waiting on a queue, semaphore, mutex or event group.
+///
+/// Every waiting future owns a [`Registration`], a node of an intrusive list that lives
+/// in the pinned future. Waking wakes all registered futures; those that still can't
+/// make progress register again.
+///
+/// The list is only accessed in critical regions, which never block, so it can be
+/// woken from interrupts. Interrupts wake in place; wakers are only cloned and dropped
+/// by tasks.
+pub(crate) struct WakerList {
+    state: UnsafeCell<ListState>,
+}
+
+struct ListState {
+    head: *mut WaitNode,
+    /// Incremented by every [`WakerList::wake`], see [`WaitNode::epoch`].
+    epoch: u32,
+}
+
+struct WaitNode {
+    waker: Option<Waker>,
+    prev: *mut WaitNode,
+    next: *mut WaitNode,
+    linked: bool,
+    /// Epoch of the list when the waker was registered. A wake only takes the wakers
+    /// registered before it started, so futures registering again while it runs don't
+    /// keep it going.
+    epoch: u32,
+}
+
+unsafe impl Send for WakerList {}
+unsafe impl Sync for WakerList {}
+
+impl WakerList {
+    pub(crate) const fn new() -> WakerList {
+        WakerList {
+            state: UnsafeCell::new(ListState {
+                head: ptr::null_mut(),
+                epoch: 0,
+            }),
+        }
+    }
+
+    /// Get the wait node of one future. It is linked into the list by its first
+    /// [`Registration::register`] and unlinked when dropped, so it must be pinned.
+    pub(crate) fn registration(&self) -> Registration<'_> {
+        Registration {
+            list: self,
+            node: UnsafeCell::new(WaitNode {
+                waker: None,
+                prev: ptr::null_mut(),
+                next: ptr::null_mut(),
+                linked: false,
+                epoch: 0,
+            }),
+            _pinned: PhantomPinned,
+        }
+    }
+
+    pub(crate) fn wake(&self) {
+        let epoch = {
+            let _lock = CriticalRegion::enter();
+            let state = unsafe { &mut *self.state.get() };
+            state.epoch = state.epoch.wrapping_add(1);
+            state.epoch
+        };
+
+        // Wakers are taken one at a time and woken outside of the critical region.
+        loop {
+            let waker = {
+                let _lock = CriticalRegion::enter();
+                unsafe { self.take_registered_before(epoch) }
+            };
+
+            match waker {
+                Some(waker) => waker.wake(),
+                None => break,
+            }
+        }
+    }
+
+    pub(crate) fn wake_from_isr(&self, context: &mut InterruptContext) {
+        let _lock = CriticalRegionIsr::enter(context);
+        unsafe {
+            self.for_each_waker(|waker| wake_from_isr(waker, context));
+        }
+    }
+
+    /// Wake the futures from the timer daemon task, after the commands that the
+    /// interrupt queued before.
+    pub(crate) fn pend_wake_from_isr(&self, context: &mut InterruptContext) {
+        let _lock = CriticalRegionIsr::enter(context);
+        unsafe {
+            self.for_each_waker(|waker| pend_wake_from_isr(waker, context));
+        }
+    }
+
+    /// Must be called in a critical region.
+    unsafe fn take_registered_before(&self, epoch: u32) -> Option<Waker> {
+        let mut node = (*self.state.get()).head;
+        while !node.is_null() {
+            let n = &mut *node;
+            if n.waker.is_some() && (epoch.wrapping_sub(n.epoch) as i32) > 0 {
+                return n.waker.take();
+            }
+            node = n.next;
+        }
+        None
+    }
+
+    /// Must be called in a critical region.
+    unsafe fn for_each_waker(&self, mut f: impl FnMut(&Waker)) {
+        let mut node = (*self.state.get()).head;
+        while !node.is_null() {
+            if let Some(waker) = &(*node).waker {
+                f(waker);
+            }
+            node = (*node).next;
+        }
+    }
+}
+
+impl fmt::Debug for WakerList {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("WakerList")
+    }
+}
+
+/// The wait node of one future in a [`WakerList`]. Unlinks the node and forgets its
+/// waker when dropped, so the object doesn't keep the waker of a future that completed
+/// or was dropped. Other futures waiting on the object are not affected.
+pub(crate) struct Registration<'a> {
+    list: &'a WakerList,
+    node: UnsafeCell<WaitNode>,
+    _pinned: PhantomPinned,
+}
+
+// The node is only accessed in critical regions of the list.
+unsafe impl Send for Registration<'_> {}
+unsafe impl Sync for Registration<'_> {}
+
+impl Registration<'_> {
+    pub(crate) fn register(self: Pin<&Self>, waker: &Waker) {
+        let waker = Some(waker.clone());
+
+        let old = {
+            let _lock = CriticalRegion::enter();
+            unsafe {
+                let state = &mut *self.list.state.get();
+                let node = self.node.get();
+                if !(*node).linked {
+                    (*node).next = state.head;
+                    if !state.head.is_null() {
+                        (*state.head).prev = node;
+                    }
+                    state.head = node;
+                    (*node).linked = true;
+                }
+                (*node).epoch = state.epoch;
+                mem::replace(&mut (*node).waker, waker)
+            }
+        };
+        drop(old);
+    }
+}
+
+impl Drop for Registration<'_> {
+    fn drop(&mut self) {
+        let old = {
+            let _lock = CriticalRegion::enter();
+            unsafe {
+                let state = &mut *self.list.state.get();
+                let node = self.node.get();
+                if (*node).linked {
+                    let (prev, next) = ((*node).prev, (*node).next);
+                    if prev.is_null() {
+                        state.head = next;
+                    } else {
+                        (*prev).next = next;
+                    }
+                    if !next.is_null() {
+                        (*next).prev = prev;
+                    }
+                    (*node).linked = false;
+                }
+                (*node).waker.take()
+            }
+        };
+        drop(old);
+    }
+}